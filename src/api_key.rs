@@ -0,0 +1,69 @@
+use std::{env, fmt, fs, io, path::Path};
+
+use zeroize::Zeroizing;
+
+/// A jpdb API key, found in the settings page.
+///
+/// It's redacted when printed with `{}` or `{:?}`, and zeroed out of memory when dropped, so that
+/// logging a [`Client`](crate::client::Client) doesn't leak it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(Zeroizing<String>);
+
+impl ApiKey {
+    /// Name of the environment variable read by [`ApiKey::from_env`]
+    pub const ENV_VAR: &'static str = "JPDB_TOKEN";
+
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(Zeroizing::new(key.into()))
+    }
+
+    /// Reads the key from the `JPDB_TOKEN` environment variable
+    pub fn from_env() -> Result<Self, env::VarError> {
+        Self::from_env_var(Self::ENV_VAR)
+    }
+
+    pub fn from_env_var(name: &str) -> Result<Self, env::VarError> {
+        env::var(name).map(Self::new)
+    }
+
+    /// Reads the key from a file, ignoring surrounding whitespace such as a trailing newline
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = Zeroizing::new(fs::read_to_string(path)?);
+        Ok(Self::new(content.trim()))
+    }
+
+    /// Value of the `Authorization` header, the only place the key is exposed
+    pub(crate) fn authorization(&self) -> Zeroizing<String> {
+        Zeroizing::new(format!("Bearer {}", self.0.as_str()))
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey([redacted])")
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<&String> for ApiKey {
+    fn from(key: &String) -> Self {
+        Self::new(key.as_str())
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self::new(key)
+    }
+}
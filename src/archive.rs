@@ -0,0 +1,293 @@
+//! Backs up every deck of an account to a portable file, and restores it, see
+//! [`Client::export_account`](crate::client::Client::export_account) and
+//! [`Client::import_account`](crate::client::Client::import_account).
+//!
+//! ```no_run
+//! use jpdb::{archive::{AccountArchive, ImportLog, ImportOptions}, client::Client};
+//!
+//! let archive = Client::new("old_token").export_account()?;
+//! std::fs::write("jpdb.json", archive.to_json()?)?;
+//!
+//! let archive = AccountArchive::from_json(&std::fs::read_to_string("jpdb.json")?)?;
+//! let mut log = ImportLog::default();
+//! // On failure, `log` can be saved and passed again to resume where the import stopped
+//! Client::new("new_token").import_account(&archive, ImportOptions::default(), &mut log)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "async")]
+use crate::async_client::AsyncClient;
+use crate::{
+    batch,
+    client::Client,
+    error::Error,
+    request::{
+        AddVocabularyOptions, AnyDeckId, AnyDeckWidget, Deck, DeckQueryField, DeckVocabulary,
+        Vocabulary,
+    },
+};
+
+/// Bumped whenever the format of [`AccountArchive`] changes in a way older versions can't read
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountArchive {
+    pub version: u32,
+    /// The user decks in the order jpdb lists them, followed by the special decks
+    pub decks: Vec<ArchivedDeck>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedDeck {
+    /// The id the deck had in the exported account
+    pub id: AnyDeckWidget,
+    pub name: String,
+    /// Position among the user decks, `None` for the special ones
+    pub position: Option<usize>,
+    pub vocabulary: Vec<Vocabulary>,
+    /// As many as `vocabulary`
+    pub occurences: Vec<u32>,
+}
+
+impl ArchivedDeck {
+    pub(crate) fn new(
+        id: AnyDeckWidget,
+        deck: Deck,
+        position: Option<usize>,
+        vocabulary: DeckVocabulary,
+    ) -> Result<Self, Error> {
+        let (vocabulary, occurences) = vocabulary.into_occurence_list()?.into_iter().unzip();
+        Ok(Self {
+            id,
+            name: deck.name.unwrap_or_default(),
+            position,
+            vocabulary,
+            occurences,
+        })
+    }
+
+    fn is_special(&self) -> bool {
+        !matches!(self.id, AnyDeckWidget::UserDeckId(_))
+    }
+}
+
+impl AccountArchive {
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(Error::SerializeError)
+    }
+
+    /// Fails with [`Error::BadArchive`] if the archive was written by a newer version of this
+    /// crate
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+
+        let Version { version } =
+            serde_json::from_str(json).map_err(|e| Error::DeserializeError(e.into()))?;
+        if version > ARCHIVE_VERSION {
+            return Err(Error::BadArchive(format!(
+                "archive version {version} is newer than the supported {ARCHIVE_VERSION}"
+            )));
+        }
+        serde_json::from_str(json).map_err(|e| Error::DeserializeError(e.into()))
+    }
+}
+
+/// What to do with an archived deck when the account already has one with the same name, or
+/// when the special deck it restores isn't empty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Leaves the existing deck as it is
+    #[default]
+    Skip,
+    /// Adds the archived words to the existing deck, overwriting the occurences of the ones it
+    /// already has
+    Merge,
+    /// Clears the existing deck before adding the archived words
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportOptions {
+    pub on_conflict: ConflictPolicy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportAction {
+    Created,
+    Skipped,
+    Merged,
+    Replaced,
+}
+
+/// Progress of [`Client::import_account`](crate::client::Client::import_account), with an entry
+/// per archived deck that was started, in the order of the archive.
+///
+/// It can be serialized alongside the archive, so that an interrupted import is resumed with the
+/// same log instead of creating its decks twice.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportLog {
+    pub decks: Vec<ImportedDeck>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ImportedDeck {
+    pub name: String,
+    /// The id of the deck in the account the archive was imported to
+    pub id: AnyDeckWidget,
+    pub action: ImportAction,
+    /// Whether its words were added, or it was skipped
+    pub done: bool,
+}
+
+/// How an archived deck is to be imported, given the decks the account already has
+pub(crate) enum Target {
+    Existing(AnyDeckWidget, ImportAction),
+    /// A user deck to create at the given position
+    New(Option<u8>),
+}
+
+pub(crate) fn target(
+    deck: &ArchivedDeck,
+    existing: &[(AnyDeckWidget, Deck)],
+    options: ImportOptions,
+) -> Target {
+    let conflict = |id: AnyDeckWidget| match options.on_conflict {
+        ConflictPolicy::Skip => Target::Existing(id, ImportAction::Skipped),
+        ConflictPolicy::Merge => Target::Existing(id, ImportAction::Merged),
+        ConflictPolicy::Replace => Target::Existing(id, ImportAction::Replaced),
+    };
+    if deck.is_special() {
+        let count = existing
+            .iter()
+            .find(|(id, _)| *id == deck.id)
+            .and_then(|(_, existing)| existing.vocabulary_count);
+        return match count {
+            Some(count) if count > 0 => conflict(deck.id.clone()),
+            _ => Target::Existing(deck.id.clone(), ImportAction::Merged),
+        };
+    }
+    let same_name = existing.iter().find(|(id, existing)| {
+        matches!(id, AnyDeckWidget::UserDeckId(_))
+            && existing.name.as_deref() == Some(deck.name.as_str())
+    });
+    match same_name {
+        Some((id, _)) => conflict(id.clone()),
+        None => Target::New(deck.position.and_then(|p| u8::try_from(p).ok())),
+    }
+}
+
+/// The log entry of the archived deck at `index`, if it was started already
+pub(crate) fn logged<'a>(
+    log: &'a ImportLog,
+    index: usize,
+    deck: &ArchivedDeck,
+) -> Result<Option<&'a ImportedDeck>, Error> {
+    match log.decks.get(index) {
+        Some(entry) if entry.name != deck.name => Err(Error::BadArchive(format!(
+            "expected deck \"{}\" at {index}, but the log has \"{}\"",
+            deck.name, entry.name
+        ))),
+        entry => Ok(entry),
+    }
+}
+
+pub(crate) fn add_occurences(deck: &ArchivedDeck) -> Vec<u16> {
+    batch::cap_occurences(deck.occurences.iter().copied())
+}
+
+pub(crate) fn add_options<'a>(
+    deck: &'a ArchivedDeck,
+    occurences: &'a [u16],
+) -> AddVocabularyOptions<'a> {
+    AddVocabularyOptions {
+        vocabulary: &deck.vocabulary,
+        occurences: Some(occurences),
+        overwrite_occurences: Some(true),
+        ..Default::default()
+    }
+}
+
+/// Implements the export and import for a client, with `async await` for [`AsyncClient`], so that
+/// both clients resolve and log the decks the same way
+macro_rules! impl_archive {
+    ($client:ty $(, $async:tt $await:tt)?) => {
+        impl $client {
+            /// Snapshots every deck of the account, user and special, with the occurences of its
+            /// words
+            pub $($async)? fn export_account(&self) -> Result<AccountArchive, Error> {
+                let decks = self.list_all_decks(&[DeckQueryField::Name]) $(.$await)? ?;
+                let mut archived = Vec::with_capacity(decks.len());
+                for (index, (id, deck)) in decks.into_iter().enumerate() {
+                    let position = matches!(id, AnyDeckWidget::UserDeckId(_)).then_some(index);
+                    let vocabulary = self.list_vocabulary_raw(&id, Some(true)) $(.$await)? ?;
+                    archived.push(ArchivedDeck::new(id, deck, position, vocabulary)?);
+                }
+                Ok(AccountArchive {
+                    version: ARCHIVE_VERSION,
+                    decks: archived,
+                })
+            }
+
+            /// Restores the decks of `archive`, creating the user decks the account doesn't have
+            /// yet and resolving the others according to [`ImportOptions::on_conflict`].
+            ///
+            /// `log` is updated as decks are imported. Passing it again after a failure resumes
+            /// the import instead of starting over, so it should start out empty for a new import.
+            /// Fails with [`Error::BadArchive`] if it was written for another archive.
+            pub $($async)? fn import_account(
+                &self,
+                archive: &AccountArchive,
+                options: ImportOptions,
+                log: &mut ImportLog,
+            ) -> Result<(), Error> {
+                let existing = self
+                    .list_all_decks(&[DeckQueryField::Name, DeckQueryField::VocabularyCount])
+                    $(.$await)? ?;
+                for (index, deck) in archive.decks.iter().enumerate() {
+                    let (id, action) = match logged(log, index, deck)? {
+                        Some(entry) if entry.done => continue,
+                        Some(entry) => (entry.id.clone(), entry.action),
+                        None => {
+                            let (id, action) = match target(deck, &existing, options) {
+                                Target::Existing(id, action) => (id, action),
+                                Target::New(position) => {
+                                    let id = self
+                                        .create_empty_deck(&deck.name, position)
+                                        $(.$await)? ?;
+                                    (id.as_any(), ImportAction::Created)
+                                }
+                            };
+                            log.decks.push(ImportedDeck {
+                                name: deck.name.clone(),
+                                id: id.clone(),
+                                action,
+                                done: false,
+                            });
+                            (id, action)
+                        }
+                    };
+                    if action == ImportAction::Replaced {
+                        self.clear_deck(&id) $(.$await)? ?;
+                    }
+                    if action != ImportAction::Skipped && !deck.vocabulary.is_empty() {
+                        let occurences = add_occurences(deck);
+                        self.add_vocabulary(id, &add_options(deck, &occurences))
+                            $(.$await)? ?;
+                    }
+                    log.decks[index].done = true;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_archive!(Client);
+#[cfg(feature = "async")]
+impl_archive!(AsyncClient, async await);
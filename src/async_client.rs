@@ -0,0 +1,423 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::{
+    api_key::ApiKey,
+    batch::DEFAULT_CHUNK_SIZE,
+    client::{Client, DEFAULT_BASE_URL},
+    endpoint::{self, Endpoint},
+    error::Error,
+    governor::{RateLimit, RateLimiter, RetryPolicy},
+    request::{
+        dedup_fields, tag_decks, with_id_field, AddVocabularyOptions, AnyDeckId, AnyDeckWidget,
+        Deck, DeckQueryField, DeckTypeToList, DeckVocabulary, ParseResult, Request,
+        SetCardSentenceOptions, Sid, TokenQueryField, UserDeckId, Vid, VocabQueryField, Vocabulary,
+        VocabularyEntry,
+    },
+    transport::{AsyncTransport, HttpResponse, ReqwestTransport},
+};
+
+/// Asynchronous counterpart of [`Client`], available with the `async` feature.
+///
+/// It exposes the same endpoints, and returns the same errors. Waiting for the rate limit or
+/// before a retry needs a Tokio runtime, which the default [`ReqwestTransport`] needs anyway.
+#[derive(Debug, Clone)]
+pub struct AsyncClient {
+    key: ApiKey,
+    transport: Arc<dyn AsyncTransport>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry: Option<RetryPolicy>,
+    pub(crate) chunk_size: usize,
+    pub(crate) base_url: String,
+}
+
+impl AsyncClient {
+    /// Fails with [`Error::Transport`] if the TLS backend couldn't be initialized
+    pub fn new(key: impl Into<ApiKey>) -> Result<Self, Error> {
+        Ok(Self::with_transport(key, ReqwestTransport::new()?))
+    }
+
+    /// Starts building a client with more options than [`AsyncClient::new`]
+    pub fn builder(key: impl Into<ApiKey>) -> AsyncClientBuilder {
+        AsyncClientBuilder::new(key)
+    }
+
+    /// Creates a client that sends its requests through `transport` instead of the default
+    /// [`ReqwestTransport`]
+    pub fn with_transport(
+        key: impl Into<ApiKey>,
+        transport: impl AsyncTransport + 'static,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            transport: Arc::new(transport),
+            rate_limiter: None,
+            retry: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            base_url: DEFAULT_BASE_URL.to_owned(),
+        }
+    }
+
+    /// Sends the request, going through the rate limit and retry policy if they were configured
+    pub(crate) async fn send_request(
+        &self,
+        prepared: Request,
+        idempotent: bool,
+    ) -> Result<HttpResponse, Error> {
+        let authorization = self.key.authorization();
+        let headers = [("Authorization", authorization.as_str())];
+        let mut attempt = 0;
+        loop {
+            if let Some(ref limiter) = self.rate_limiter {
+                limiter.acquire_async().await;
+            }
+            let (error, retry_after) = match self.transport.send(&prepared, &headers).await {
+                Ok(response) if response.is_success() => return Ok(response),
+                Ok(response) => {
+                    let retry_after = response.retry_after();
+                    (response.into_error(), retry_after)
+                }
+                Err(e) => (Error::Transport(e), None),
+            };
+            match self.retry {
+                Some(ref policy) if policy.should_retry(&error, attempt, idempotent) => {
+                    tokio::time::sleep(policy.delay(attempt, retry_after)).await;
+                    attempt += 1;
+                }
+                _ => return Err(error),
+            }
+        }
+    }
+
+    /// See [`Client::execute`]
+    pub async fn execute<E: Endpoint>(&self, endpoint: &E) -> Result<E::Response, Error> {
+        endpoint.validate()?;
+        let request = Request::new(&self.base_url, endpoint)?;
+        self.send_request(request, endpoint.is_idempotent())
+            .await?
+            .into_json::<E::Response>()
+            .map_err(Error::DeserializeError)
+    }
+
+    /// See [`Client::call_raw`]
+    pub async fn call_raw(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, Error> {
+        let request = Request {
+            url: Client::create_url(&self.base_url, path.trim_start_matches('/')),
+            body: body.clone(),
+        };
+        self.send_request(request, false)
+            .await?
+            .into_json()
+            .map_err(Error::DeserializeError)
+    }
+
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.execute(&endpoint::Ping).await?;
+        Ok(())
+    }
+
+    pub async fn parse_text(
+        &self,
+        text: &str,
+        token_fields: &[TokenQueryField],
+        vocab_fields: Option<&[VocabQueryField]>,
+    ) -> Result<ParseResult, Error> {
+        let token_fields = dedup_fields(token_fields);
+        let vocab_fields = dedup_fields(vocab_fields.unwrap_or_default());
+        let response = self
+            .execute(&endpoint::ParseText {
+                text,
+                token_fields: &token_fields,
+                vocabulary_fields: &vocab_fields,
+            })
+            .await?;
+        ParseResult::from_response(&token_fields, &vocab_fields, response)
+    }
+
+    pub async fn lookup_vocabulary(
+        &self,
+        list: &[(Vid, Sid)],
+        fields: &[VocabQueryField],
+    ) -> Result<HashMap<(Vid, Sid), Option<VocabularyEntry>>, Error> {
+        let fields = dedup_fields(fields);
+        let response = self
+            .execute(&endpoint::LookupVocabulary {
+                list,
+                fields: &fields,
+            })
+            .await?;
+        response.into_entries(list, &fields)
+    }
+
+    pub async fn list_decks_raw(
+        &self,
+        fields: &[DeckQueryField],
+        deck_type: DeckTypeToList,
+    ) -> Result<Vec<Deck>, Error> {
+        let fields = dedup_fields(fields);
+        let response = match deck_type {
+            DeckTypeToList::UserDecks => {
+                self.execute(&endpoint::ListUserDecks { fields: &fields })
+                    .await
+            }
+            DeckTypeToList::SpecialDecks => {
+                self.execute(&endpoint::ListSpecialDecks { fields: &fields })
+                    .await
+            }
+        }?;
+        response.into_decks(&fields)
+    }
+
+    pub async fn list_special_decks(&self, fields: &[DeckQueryField]) -> Result<Vec<Deck>, Error> {
+        self.list_decks_raw(fields, DeckTypeToList::SpecialDecks)
+            .await
+    }
+
+    pub async fn list_user_decks(&self, fields: &[DeckQueryField]) -> Result<Vec<Deck>, Error> {
+        self.list_decks_raw(fields, DeckTypeToList::UserDecks).await
+    }
+
+    /// See [`Client::list_all_decks`]
+    pub async fn list_all_decks(
+        &self,
+        fields: &[DeckQueryField],
+    ) -> Result<Vec<(AnyDeckWidget, Deck)>, Error> {
+        let fields = with_id_field(fields);
+        let mut decks = self.list_user_decks(&fields).await?;
+        decks.extend(self.list_special_decks(&fields).await?);
+        tag_decks(decks)
+    }
+
+    pub async fn create_empty_deck(
+        &self,
+        name: &str,
+        position: Option<u8>,
+    ) -> Result<UserDeckId, Error> {
+        let response = self
+            .execute(&endpoint::CreateEmptyDeck { name, position })
+            .await?;
+        Ok(response.into())
+    }
+
+    pub async fn list_vocabulary_raw(
+        &self,
+        deck_id: impl AnyDeckId,
+        fetch_occurence: Option<bool>,
+    ) -> Result<DeckVocabulary, Error> {
+        self.execute(&endpoint::ListVocabulary {
+            id: deck_id.as_any(),
+            fetch_occurences: fetch_occurence,
+        })
+        .await
+    }
+
+    pub async fn list_vocabulary(&self, deck_id: impl AnyDeckId) -> Result<Vec<Vocabulary>, Error> {
+        let raw = self.list_vocabulary_raw(deck_id, None).await?;
+        Ok(raw.vocabulary)
+    }
+
+    pub async fn list_vocabulary_with_occurences(
+        &self,
+        deck_id: impl AnyDeckId,
+    ) -> Result<HashMap<Vocabulary, u32>, Error> {
+        self.list_vocabulary_raw(deck_id, Some(true))
+            .await?
+            .into_occurence_map()
+    }
+
+    pub async fn add_vocabulary(
+        &self,
+        deck_id: impl AnyDeckId,
+        options: &AddVocabularyOptions<'_>,
+    ) -> Result<(), Error> {
+        self.add_vocabulary_with_progress(deck_id, options, |_| {})
+            .await
+    }
+
+    pub async fn remove_vocabulary(
+        &self,
+        deck_id: impl AnyDeckId,
+        vocabulary: &[Vocabulary],
+    ) -> Result<(), Error> {
+        self.remove_vocabulary_with_progress(deck_id, vocabulary, |_| {})
+            .await
+    }
+
+    pub async fn clear_deck(&self, deck_id: impl AnyDeckId) -> Result<(), Error> {
+        self.execute(&endpoint::ClearDeck {
+            id: deck_id.as_any(),
+        })
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_deck(&self, deck_id: UserDeckId) -> Result<(), Error> {
+        self.execute(&endpoint::DeleteDeck {
+            id: deck_id.as_any(),
+        })
+        .await?;
+        Ok(())
+    }
+
+    pub async fn rename_deck(&self, deck_id: UserDeckId, new_name: &str) -> Result<(), Error> {
+        self.execute(&endpoint::RenameDeck {
+            id: deck_id.as_any(),
+            name: new_name,
+        })
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_card_sentence(
+        &self,
+        options: &SetCardSentenceOptions<'_>,
+    ) -> Result<(), Error> {
+        self.execute(options).await?;
+        Ok(())
+    }
+}
+
+/// Builder for an [`AsyncClient`], the counterpart of
+/// [`ClientBuilder`](crate::client::ClientBuilder).
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use jpdb::{async_client::AsyncClient, governor::RetryPolicy};
+///
+/// let client = AsyncClient::builder("my_jpdb_token")
+///     .timeout(Duration::from_secs(120))
+///     .retry(RetryPolicy::default())
+///     .build()?;
+/// # Ok::<(), jpdb::error::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncClientBuilder {
+    key: ApiKey,
+    base_url: String,
+    timeout_connect: Duration,
+    timeout: Duration,
+    user_agent: Option<String>,
+    proxy: Option<String>,
+    gzip: bool,
+    transport: Option<Arc<dyn AsyncTransport>>,
+    rate_limit: Option<RateLimit>,
+    retry: Option<RetryPolicy>,
+    chunk_size: usize,
+}
+
+impl AsyncClientBuilder {
+    pub fn new(key: impl Into<ApiKey>) -> Self {
+        Self {
+            key: key.into(),
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            timeout_connect: Duration::from_secs(5),
+            timeout: Duration::from_secs(20),
+            user_agent: None,
+            proxy: None,
+            gzip: true,
+            transport: None,
+            rate_limit: None,
+            retry: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// See [`ClientBuilder::base_url`](crate::client::ClientBuilder::base_url)
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_owned();
+        if !self.base_url.ends_with('/') {
+            self.base_url.push('/');
+        }
+        self
+    }
+
+    /// Timeout for establishing the connection, 5 seconds by default
+    pub fn timeout_connect(mut self, timeout: Duration) -> Self {
+        self.timeout_connect = timeout;
+        self
+    }
+
+    /// Timeout for the whole request, 20 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_owned());
+        self
+    }
+
+    /// Proxy to send the requests through, in the format accepted by [`reqwest::Proxy::all`]
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_owned());
+        self
+    }
+
+    /// Whether responses may be gzip compressed, enabled by default
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    /// Sends the requests through `transport`.
+    ///
+    /// The timeouts, user agent, proxy and gzip options only apply to the default
+    /// [`ReqwestTransport`], and are ignored when a transport is given.
+    pub fn transport(mut self, transport: impl AsyncTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// See [`ClientBuilder::rate_limit`](crate::client::ClientBuilder::rate_limit)
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// See [`ClientBuilder::retry`](crate::client::ClientBuilder::retry)
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// See [`ClientBuilder::chunk_size`](crate::client::ClientBuilder::chunk_size)
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Fails with [`Error::Transport`] if the proxy couldn't be parsed, or the TLS backend couldn't
+    /// be initialized
+    pub fn build(self) -> Result<AsyncClient, Error> {
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => {
+                let mut client = reqwest::Client::builder()
+                    .connect_timeout(self.timeout_connect)
+                    .timeout(self.timeout)
+                    .gzip(self.gzip);
+                if let Some(ref user_agent) = self.user_agent {
+                    client = client.user_agent(user_agent);
+                }
+                if let Some(ref proxy) = self.proxy {
+                    client = client.proxy(reqwest::Proxy::all(proxy)?);
+                }
+                Arc::new(ReqwestTransport::from_client(client.build()?))
+            }
+        };
+        Ok(AsyncClient {
+            key: self.key,
+            transport,
+            rate_limiter: self
+                .rate_limit
+                .map(|limit| Arc::new(RateLimiter::new(limit))),
+            retry: self.retry,
+            chunk_size: self.chunk_size,
+            base_url: self.base_url,
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use std::ops::Range;
+
+#[cfg(feature = "async")]
+use crate::async_client::AsyncClient;
+use crate::{
+    client::Client,
+    endpoint::{AddVocabulary, Endpoint, RemoveVocabulary},
+    error::Error,
+    request::{AddVocabularyOptions, AnyDeckId, AnyDeckWidget, Vocabulary},
+};
+
+/// How many words [`Client::add_vocabulary`](crate::client::Client::add_vocabulary) and
+/// [`Client::remove_vocabulary`](crate::client::Client::remove_vocabulary) send per request,
+/// unless configured otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+/// Reported after every chunk of a batch was applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkProgress {
+    /// Starts at 1
+    pub chunk: usize,
+    pub chunks: usize,
+    /// How many words were applied so far, this chunk included
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Splits `0..len` in ranges of at most `size`. An empty input still gets a single empty range,
+/// so that the request is sent, and fails, the same way it would without chunking.
+fn chunk_ranges(len: usize, size: usize) -> Vec<Range<usize>> {
+    let size = size.max(1);
+    (0..len.max(1))
+        .step_by(size)
+        .map(|start| start..(start + size).min(len))
+        .collect()
+}
+
+/// `options` is expected to be validated already, so that `occurences` can be sliced like `vocabulary`
+pub(crate) fn add_vocabulary_chunks<'a>(
+    id: AnyDeckWidget,
+    options: &AddVocabularyOptions<'a>,
+    size: usize,
+) -> Vec<(Range<usize>, AddVocabulary<'a>)> {
+    chunk_ranges(options.vocabulary.len(), size)
+        .into_iter()
+        .map(|range| {
+            let chunk = AddVocabularyOptions {
+                vocabulary: &options.vocabulary[range.clone()],
+                occurences: options.occurences.map(|o| &o[range.clone()]),
+                ..*options
+            };
+            (
+                range,
+                AddVocabulary {
+                    id: id.clone(),
+                    options: chunk,
+                },
+            )
+        })
+        .collect()
+}
+
+pub(crate) fn remove_vocabulary_chunks(
+    id: AnyDeckWidget,
+    vocabulary: &[Vocabulary],
+    size: usize,
+) -> Vec<(Range<usize>, RemoveVocabulary<'_>)> {
+    chunk_ranges(vocabulary.len(), size)
+        .into_iter()
+        .map(|range| {
+            let chunk = RemoveVocabulary {
+                id: id.clone(),
+                vocabulary: &vocabulary[range.clone()],
+            };
+            (range, chunk)
+        })
+        .collect()
+}
+
+/// Wraps the error of a chunk with the words applied before it, if there are any
+pub(crate) fn partial_error(applied: &[Vocabulary], error: Error) -> Error {
+    if applied.is_empty() {
+        error
+    } else {
+        Error::PartialBatch(applied.to_vec(), Box::new(error))
+    }
+}
+
+/// Occurences as listed by jpdb, capped to what `add_vocabulary` accepts
+pub(crate) fn cap_occurences(occurences: impl IntoIterator<Item = u32>) -> Vec<u16> {
+    occurences
+        .into_iter()
+        .map(|o| u16::try_from(o).unwrap_or(u16::MAX))
+        .collect()
+}
+
+/// Implements the chunked calls for a client, with `async await` for [`AsyncClient`], so that
+/// both clients share the same steps and only differ in how they wait for a response
+macro_rules! impl_batch {
+    ($client:ty $(, $async:tt $await:tt)?) => {
+        impl $client {
+            /// Adds the vocabulary in chunks, calling `progress` after each of them.
+            ///
+            /// If a chunk fails after others were applied, the error is wrapped in
+            /// [`Error::PartialBatch`] along with the words that were added.
+            pub $($async)? fn add_vocabulary_with_progress(
+                &self,
+                deck_id: impl AnyDeckId,
+                options: &AddVocabularyOptions<'_>,
+                mut progress: impl FnMut(ChunkProgress),
+            ) -> Result<(), Error> {
+                let id = deck_id.as_any();
+                AddVocabulary {
+                    id: id.clone(),
+                    options: *options,
+                }
+                .validate()?;
+                let chunks = add_vocabulary_chunks(id, options, self.chunk_size);
+                let count = chunks.len();
+                for (index, (range, chunk)) in chunks.into_iter().enumerate() {
+                    self.execute(&chunk)
+                        $(.$await)?
+                        .map_err(|e| partial_error(&options.vocabulary[..range.start], e))?;
+                    progress(ChunkProgress {
+                        chunk: index + 1,
+                        chunks: count,
+                        done: range.end,
+                        total: options.vocabulary.len(),
+                    });
+                }
+                Ok(())
+            }
+
+            /// Removes the vocabulary in chunks, calling `progress` after each of them.
+            ///
+            /// If a chunk fails after others were applied, the error is wrapped in
+            /// [`Error::PartialBatch`] along with the words that were removed.
+            pub $($async)? fn remove_vocabulary_with_progress(
+                &self,
+                deck_id: impl AnyDeckId,
+                vocabulary: &[Vocabulary],
+                mut progress: impl FnMut(ChunkProgress),
+            ) -> Result<(), Error> {
+                let chunks =
+                    remove_vocabulary_chunks(deck_id.as_any(), vocabulary, self.chunk_size);
+                let count = chunks.len();
+                for (index, (range, chunk)) in chunks.into_iter().enumerate() {
+                    self.execute(&chunk)
+                        $(.$await)?
+                        .map_err(|e| partial_error(&vocabulary[..range.start], e))?;
+                    progress(ChunkProgress {
+                        chunk: index + 1,
+                        chunks: count,
+                        done: range.end,
+                        total: vocabulary.len(),
+                    });
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_batch!(Client);
+#[cfg(feature = "async")]
+impl_batch!(AsyncClient, async await);
@@ -0,0 +1,116 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A state of a card, as returned for [`VocabQueryField::CardState`](crate::request::VocabQueryField::CardState).
+///
+/// A card can be in several states at once, eg. `["due", "learning"]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CardState {
+    New,
+    Learning,
+    Known,
+    NeverForget,
+    Due,
+    Failed,
+    Locked,
+    Redundant,
+    Suspended,
+    Blacklisted,
+    /// A state this crate doesn't know about yet
+    Unknown(String),
+}
+
+impl CardState {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            CardState::New => "new",
+            CardState::Learning => "learning",
+            CardState::Known => "known",
+            CardState::NeverForget => "never-forget",
+            CardState::Due => "due",
+            CardState::Failed => "failed",
+            CardState::Locked => "locked",
+            CardState::Redundant => "redundant",
+            CardState::Suspended => "suspended",
+            CardState::Blacklisted => "blacklisted",
+            CardState::Unknown(ref s) => s,
+        }
+    }
+
+    /// Whether the word doesn't need to be studied anymore, ie. it's known or never forgotten
+    pub fn is_known(&self) -> bool {
+        matches!(self, CardState::Known | CardState::NeverForget)
+    }
+}
+
+impl From<&str> for CardState {
+    fn from(s: &str) -> Self {
+        match s {
+            "new" => CardState::New,
+            "learning" => CardState::Learning,
+            "known" => CardState::Known,
+            "never-forget" => CardState::NeverForget,
+            "due" => CardState::Due,
+            "failed" => CardState::Failed,
+            "locked" => CardState::Locked,
+            "redundant" => CardState::Redundant,
+            "suspended" => CardState::Suspended,
+            "blacklisted" => CardState::Blacklisted,
+            other => CardState::Unknown(other.to_owned()),
+        }
+    }
+}
+
+impl std::fmt::Display for CardState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for CardState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CardState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(CardState::from(s.as_str()))
+    }
+}
+
+/// When a card is due, as returned for [`VocabQueryField::DueAt`](crate::request::VocabQueryField::DueAt).
+///
+/// Converts to [`SystemTime`], and to `chrono` or `time` types with the features of the same name.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DueAt(
+    /// Seconds since the Unix epoch
+    pub u64,
+);
+
+impl DueAt {
+    /// `None` if the timestamp is out of the platform's range
+    pub fn to_system_time(self) -> Option<SystemTime> {
+        UNIX_EPOCH.checked_add(Duration::from_secs(self.0))
+    }
+
+    /// `None` if the timestamp is out of chrono's range
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(i64::try_from(self.0).ok()?, 0)
+    }
+
+    /// `None` if the timestamp is out of time's range
+    #[cfg(feature = "time")]
+    pub fn to_offset_date_time(self) -> Option<time::OffsetDateTime> {
+        time::OffsetDateTime::from_unix_timestamp(i64::try_from(self.0).ok()?).ok()
+    }
+}
@@ -0,0 +1,305 @@
+//! Builds decks out of other decks, see
+//! [`Client::combine_decks`](crate::client::Client::combine_decks) and
+//! [`Client::move_vocabulary`](crate::client::Client::move_vocabulary).
+//!
+//! ```no_run
+//! use jpdb::{client::Client, deck_ops::{DeckTarget, SetOperation}, request::{AnyDeckId, UserDeckId}};
+//!
+//! let client = Client::new("my_jpdb_token");
+//! let (anime, core) = (UserDeckId(3), UserDeckId(1));
+//! // Words in the anime deck but not in the core one
+//! client.combine_decks(
+//!     SetOperation::Difference,
+//!     &[anime.as_any(), core.as_any()],
+//!     DeckTarget::New { name: "Anime without core", position: None },
+//! )?;
+//! # Ok::<(), jpdb::error::Error>(())
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "async")]
+use crate::async_client::AsyncClient;
+use crate::{
+    batch,
+    client::Client,
+    error::Error,
+    request::{AddVocabularyOptions, AnyDeckId, AnyDeckWidget, Vocabulary},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperation {
+    /// The words in any of the decks, with their occurences summed
+    Union,
+    /// The words in every deck, with their occurences summed
+    Intersection,
+    /// The words of the first deck that none of the others have, with their occurences
+    Difference,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckTarget<'a> {
+    /// Creates a user deck, see [`Client::create_empty_deck`](crate::client::Client::create_empty_deck)
+    New { name: &'a str, position: Option<u8> },
+    /// Adds to a deck, summing the occurences of the words it already has. If the deck is one of
+    /// the combined ones too, its own occurences aren't counted twice, and its words that aren't
+    /// part of the result are left in it.
+    Existing(AnyDeckWidget),
+}
+
+pub(crate) fn no_decks() -> Error {
+    Error::InvalidRequest("decks", String::from("at least one deck is needed"))
+}
+
+/// Applies `operation` to the contents of the decks, keeping the words in the order they first
+/// appear in
+pub(crate) fn combine(
+    operation: SetOperation,
+    decks: &[Vec<(Vocabulary, u32)>],
+) -> Vec<(Vocabulary, u32)> {
+    let mut result: Vec<(Vocabulary, u32)> = Vec::new();
+    let mut indices = HashMap::<Vocabulary, usize>::new();
+    let mut counts = HashMap::<Vocabulary, usize>::new();
+    for deck in decks {
+        for &(vocabulary, occurences) in deck {
+            *counts.entry(vocabulary).or_default() += 1;
+            match indices.get(&vocabulary) {
+                Some(&index) => {
+                    let total = &mut result[index].1;
+                    *total = total.saturating_add(occurences);
+                }
+                None => {
+                    indices.insert(vocabulary, result.len());
+                    result.push((vocabulary, occurences));
+                }
+            }
+        }
+    }
+    match operation {
+        SetOperation::Union => result,
+        SetOperation::Intersection => result
+            .into_iter()
+            .filter(|(vocabulary, _)| counts[vocabulary] == decks.len())
+            .collect(),
+        SetOperation::Difference => {
+            let Some((first, others)) = decks.split_first() else {
+                return Vec::new();
+            };
+            let excluded: HashSet<Vocabulary> = others
+                .iter()
+                .flat_map(|deck| deck.iter().map(|&(vocabulary, _)| vocabulary))
+                .collect();
+            first
+                .iter()
+                .filter(|(vocabulary, _)| !excluded.contains(vocabulary))
+                .copied()
+                .collect()
+        }
+    }
+}
+
+/// What's left to add to `target` for it to hold `combined`, when `target` is one of the decks
+/// `combined` was computed from and its occurences are already counted in it
+pub(crate) fn without_target(
+    combined: Vec<(Vocabulary, u32)>,
+    target: &[(Vocabulary, u32)],
+) -> Vec<(Vocabulary, u32)> {
+    let existing: HashMap<Vocabulary, u32> = target.iter().copied().collect();
+    combined
+        .into_iter()
+        .filter_map(|(vocabulary, occurences)| match existing.get(&vocabulary) {
+            Some(&own) if occurences <= own => None,
+            Some(&own) => Some((vocabulary, occurences - own)),
+            None => Some((vocabulary, occurences)),
+        })
+        .collect()
+}
+
+/// The entries of `deck` for `vocabulary`, in the order of `vocabulary`. Fails with
+/// [`Error::InvalidRequest`] if one of the words isn't in the deck.
+pub(crate) fn select(
+    deck: &[(Vocabulary, u32)],
+    vocabulary: &[Vocabulary],
+) -> Result<Vec<(Vocabulary, u32)>, Error> {
+    let occurences: HashMap<Vocabulary, u32> = deck.iter().copied().collect();
+    vocabulary
+        .iter()
+        .map(|&v| match occurences.get(&v) {
+            Some(&o) => Ok((v, o)),
+            None => Err(Error::InvalidRequest(
+                "vocabulary",
+                format!("{v:?} isn't in the source deck"),
+            )),
+        })
+        .collect()
+}
+
+/// The entries of `moved` with the occurences `target` already has for them added, to be added
+/// to `target` with `overwrite_occurences` so that sending them again doesn't count them twice
+pub(crate) fn summed(
+    moved: &[(Vocabulary, u32)],
+    target: &[(Vocabulary, u32)],
+) -> Vec<(Vocabulary, u32)> {
+    let existing: HashMap<Vocabulary, u32> = target.iter().copied().collect();
+    moved
+        .iter()
+        .map(|&(vocabulary, occurences)| {
+            let own = existing.get(&vocabulary).copied().unwrap_or(0);
+            (vocabulary, occurences.saturating_add(own))
+        })
+        .collect()
+}
+
+/// Splits entries in the words and occurences to pass to `add_vocabulary`
+pub(crate) fn split(entries: &[(Vocabulary, u32)]) -> (Vec<Vocabulary>, Vec<u16>) {
+    let vocabulary = entries.iter().map(|&(v, _)| v).collect();
+    let occurences = batch::cap_occurences(entries.iter().map(|&(_, o)| o));
+    (vocabulary, occurences)
+}
+
+pub(crate) fn check_distinct(from: &AnyDeckWidget, to: &AnyDeckWidget) -> Result<(), Error> {
+    if from == to {
+        return Err(Error::InvalidRequest(
+            "to",
+            String::from("can't move words to the deck they're in"),
+        ));
+    }
+    Ok(())
+}
+
+/// Implements the deck operations for a client, with `async await` for [`AsyncClient`], so that
+/// both clients list, combine and add the words the same way
+macro_rules! impl_deck_ops {
+    ($client:ty $(, $async:tt $await:tt)?) => {
+        impl $client {
+            /// Fills `target` with the result of `operation` over the contents of `decks`, and
+            /// returns the id of the target
+            pub $($async)? fn combine_decks(
+                &self,
+                operation: SetOperation,
+                decks: &[AnyDeckWidget],
+                target: DeckTarget<'_>,
+            ) -> Result<AnyDeckWidget, Error> {
+                if decks.is_empty() {
+                    return Err(no_decks());
+                }
+                let mut contents = Vec::with_capacity(decks.len());
+                for id in decks {
+                    contents.push(
+                        self.list_vocabulary_raw(id, Some(true))
+                            $(.$await)? ?
+                            .into_occurence_list()?,
+                    );
+                }
+                let mut combined = combine(operation, &contents);
+                if let DeckTarget::Existing(id) = &target {
+                    if let Some(index) = decks.iter().position(|deck| deck == id) {
+                        combined = without_target(combined, &contents[index]);
+                    }
+                }
+                let (vocabulary, occurences) = split(&combined);
+                let id = match target {
+                    DeckTarget::New { name, position } => {
+                        self.create_empty_deck(name, position) $(.$await)? ?.as_any()
+                    }
+                    DeckTarget::Existing(id) => id,
+                };
+                if !vocabulary.is_empty() {
+                    self.add_vocabulary(
+                        &id,
+                        &AddVocabularyOptions {
+                            vocabulary: &vocabulary,
+                            occurences: Some(&occurences),
+                            ..Default::default()
+                        },
+                    )
+                    $(.$await)? ?;
+                }
+                Ok(id)
+            }
+
+            /// Moves words to another deck along with their occurences, which are summed with the
+            /// ones `to` already has.
+            ///
+            /// Fails with [`Error::InvalidRequest`] without changing anything if one of the words
+            /// isn't in `from`. The words are added to `to` with their summed occurences, so that
+            /// the request adding them can be retried safely. They're removed from `from`
+            /// afterwards: if that fails, calling this again would count their occurences twice,
+            /// only remove them from `from` then.
+            pub $($async)? fn move_vocabulary(
+                &self,
+                from: impl AnyDeckId,
+                to: impl AnyDeckId,
+                vocabulary: &[Vocabulary],
+            ) -> Result<(), Error> {
+                let (from, to) = (from.as_any(), to.as_any());
+                check_distinct(&from, &to)?;
+                let deck = self
+                    .list_vocabulary_raw(&from, Some(true))
+                    $(.$await)? ?
+                    .into_occurence_list()?;
+                let moved = select(&deck, vocabulary)?;
+                if moved.is_empty() {
+                    return Ok(());
+                }
+                let existing = self
+                    .list_vocabulary_raw(&to, Some(true))
+                    $(.$await)? ?
+                    .into_occurence_list()?;
+                let (vocabulary, occurences) = split(&summed(&moved, &existing));
+                self.add_vocabulary(
+                    &to,
+                    &AddVocabularyOptions {
+                        vocabulary: &vocabulary,
+                        occurences: Some(&occurences),
+                        overwrite_occurences: Some(true),
+                        ..Default::default()
+                    },
+                )
+                $(.$await)? ?;
+                self.remove_vocabulary(from, &vocabulary) $(.$await)?
+            }
+
+            /// Moves every word of `from` to `to`, summing their occurences, and leaves `from`
+            /// empty.
+            ///
+            /// Like with [`Client::move_vocabulary`], calling this again after clearing `from`
+            /// failed would count the occurences twice, only clear `from` then.
+            pub $($async)? fn merge_decks(
+                &self,
+                from: impl AnyDeckId,
+                to: impl AnyDeckId,
+            ) -> Result<(), Error> {
+                let (from, to) = (from.as_any(), to.as_any());
+                check_distinct(&from, &to)?;
+                let deck = self
+                    .list_vocabulary_raw(&from, Some(true))
+                    $(.$await)? ?
+                    .into_occurence_list()?;
+                if deck.is_empty() {
+                    return Ok(());
+                }
+                let existing = self
+                    .list_vocabulary_raw(&to, Some(true))
+                    $(.$await)? ?
+                    .into_occurence_list()?;
+                let (vocabulary, occurences) = split(&summed(&deck, &existing));
+                self.add_vocabulary(
+                    &to,
+                    &AddVocabularyOptions {
+                        vocabulary: &vocabulary,
+                        occurences: Some(&occurences),
+                        overwrite_occurences: Some(true),
+                        ..Default::default()
+                    },
+                )
+                $(.$await)? ?;
+                self.clear_deck(from) $(.$await)?
+            }
+        }
+    };
+}
+
+impl_deck_ops!(Client);
+#[cfg(feature = "async")]
+impl_deck_ops!(AsyncClient, async await);
@@ -0,0 +1,320 @@
+//! Typed request/response pairs for every jpdb endpoint.
+//!
+//! The methods of [`Client`](crate::client::Client) are built on top of these, through
+//! [`Client::execute`](crate::client::Client::execute). Endpoints this crate doesn't know about yet
+//! can be added by implementing [`Endpoint`]:
+//!
+//! ```no_run
+//! use jpdb::{client::Client, endpoint::Endpoint};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize)]
+//! struct Forget {
+//!     vid: u32,
+//!     sid: u32,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Forgotten {}
+//!
+//! impl Endpoint for Forget {
+//!     const PATH: &'static str = "forget";
+//!     type Response = Forgotten;
+//! }
+//!
+//! let client = Client::new("my_jpdb_token");
+//! client.execute(&Forget { vid: 1, sid: 2 })?;
+//! # Ok::<(), jpdb::error::Error>(())
+//! ```
+
+use serde::{de::DeserializeOwned, de::IgnoredAny, Serialize};
+
+use crate::error::Error;
+use crate::request::{
+    AddVocabularyOptions, AnyDeckWidget, CreateEmptyDeckResponse, DeckQueryField, DeckVocabulary,
+    ListDecksResponse, LookupVocabularyResponse, ParseResponse, ParseTextsResponse,
+    SetCardSentenceOptions, Sid, TokenQueryField, Vid, VocabQueryField, Vocabulary,
+};
+
+/// A jpdb endpoint. `Self` is serialized as the JSON body of the request.
+pub trait Endpoint: Serialize {
+    /// Path of the endpoint, relative to the base URL of the client, eg. `deck/create-empty`
+    const PATH: &'static str;
+    /// What the endpoint answers with. Endpoints that answer with an empty object use
+    /// [`IgnoredAny`].
+    type Response: DeserializeOwned;
+
+    /// Catches what jpdb would reject before a request is spent on it, with
+    /// [`Error::InvalidRequest`]. Called by [`Client::execute`](crate::client::Client::execute).
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Whether sending the request twice has the same effect as sending it once, `false` unless
+    /// overridden. A [`RetryPolicy`](crate::governor::RetryPolicy) only retries endpoints that
+    /// aren't when they fail with
+    /// [`ErrorKind::TooManyRequests`](crate::error::ErrorKind::TooManyRequests), since after other
+    /// failures the first request may have gone through.
+    fn is_idempotent(&self) -> bool {
+        false
+    }
+}
+
+fn invalid(field: &'static str, reason: impl Into<String>) -> Error {
+    Error::InvalidRequest(field, reason.into())
+}
+
+fn validate_deck_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() {
+        return Err(invalid("name", "deck names can't be empty"));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ping;
+
+impl Endpoint for Ping {
+    const PATH: &'static str = "ping";
+    type Response = IgnoredAny;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseText<'a> {
+    pub text: &'a str,
+    pub token_fields: &'a [TokenQueryField],
+    pub vocabulary_fields: &'a [VocabQueryField],
+}
+
+impl Endpoint for ParseText<'_> {
+    const PATH: &'static str = "parse";
+    type Response = ParseResponse;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+/// Parses several texts at once, sharing a single vocabulary table
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseTexts<'a> {
+    pub text: &'a [&'a str],
+    pub token_fields: &'a [TokenQueryField],
+    pub vocabulary_fields: &'a [VocabQueryField],
+}
+
+impl Endpoint for ParseTexts<'_> {
+    const PATH: &'static str = "parse";
+    type Response = ParseTextsResponse;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LookupVocabulary<'a> {
+    pub list: &'a [(Vid, Sid)],
+    pub fields: &'a [VocabQueryField],
+}
+
+impl Endpoint for LookupVocabulary<'_> {
+    const PATH: &'static str = "lookup-vocabulary";
+    type Response = LookupVocabularyResponse;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListUserDecks<'a> {
+    pub fields: &'a [DeckQueryField],
+}
+
+impl Endpoint for ListUserDecks<'_> {
+    const PATH: &'static str = "list-user-decks";
+    type Response = ListDecksResponse;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListSpecialDecks<'a> {
+    pub fields: &'a [DeckQueryField],
+}
+
+impl Endpoint for ListSpecialDecks<'_> {
+    const PATH: &'static str = "list-special-decks";
+    type Response = ListDecksResponse;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateEmptyDeck<'a> {
+    pub name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<u8>,
+}
+
+impl Endpoint for CreateEmptyDeck<'_> {
+    const PATH: &'static str = "deck/create-empty";
+    type Response = CreateEmptyDeckResponse;
+
+    fn validate(&self) -> Result<(), Error> {
+        validate_deck_name(self.name)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ListVocabulary {
+    pub id: AnyDeckWidget,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetch_occurences: Option<bool>,
+}
+
+impl Endpoint for ListVocabulary {
+    const PATH: &'static str = "deck/list-vocabulary";
+    type Response = DeckVocabulary;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AddVocabulary<'a> {
+    pub id: AnyDeckWidget,
+    #[serde(flatten)]
+    pub options: AddVocabularyOptions<'a>,
+}
+
+impl Endpoint for AddVocabulary<'_> {
+    const PATH: &'static str = "deck/add-vocabulary";
+    type Response = IgnoredAny;
+
+    /// Unless the occurences are overwritten, they're added to the ones already in the deck
+    fn is_idempotent(&self) -> bool {
+        self.options.overwrite_occurences == Some(true)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        match self.options.occurences {
+            Some(occurences) if occurences.len() != self.options.vocabulary.len() => Err(invalid(
+                "occurences",
+                format!(
+                    "{} occurences given for {} words",
+                    occurences.len(),
+                    self.options.vocabulary.len()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RemoveVocabulary<'a> {
+    pub id: AnyDeckWidget,
+    pub vocabulary: &'a [Vocabulary],
+}
+
+impl Endpoint for RemoveVocabulary<'_> {
+    const PATH: &'static str = "deck/remove-vocabulary";
+    type Response = IgnoredAny;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClearDeck {
+    pub id: AnyDeckWidget,
+}
+
+impl Endpoint for ClearDeck {
+    const PATH: &'static str = "deck/clear";
+    type Response = IgnoredAny;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeleteDeck {
+    pub id: AnyDeckWidget,
+}
+
+impl Endpoint for DeleteDeck {
+    const PATH: &'static str = "deck/delete";
+    type Response = IgnoredAny;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RenameDeck<'a> {
+    pub id: AnyDeckWidget,
+    pub name: &'a str,
+}
+
+impl Endpoint for RenameDeck<'_> {
+    const PATH: &'static str = "deck/rename";
+    type Response = IgnoredAny;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        validate_deck_name(self.name)
+    }
+}
+
+impl Endpoint for SetCardSentenceOptions<'_> {
+    //TODO change the url in next jpdb patch
+    const PATH: &'static str = "deck/set-card-sentence";
+    type Response = IgnoredAny;
+
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        // Left at their default, they don't point to any card
+        if self.vid.0 == 0 {
+            return Err(invalid("vid", "the vocabulary id wasn't set"));
+        }
+        if self.sid.0 == 0 {
+            return Err(invalid("sid", "the spelling id wasn't set"));
+        }
+        Ok(())
+    }
+}
+
+impl SetCardSentenceOptions<'_> {
+    /// jpdb also rejects sentences that don't contain the word itself, which can only be checked
+    /// when its spelling is known, eg. from [`VocabularyEntry::spelling`](crate::request::VocabularyEntry::spelling).
+    pub fn validate_spelling(&self, spelling: &str) -> Result<(), Error> {
+        match self.sentence {
+            Some(sentence) if !sentence.contains(spelling) => Err(invalid(
+                "sentence",
+                format!("\"{spelling}\" wasn't found in the sentence"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::transport::TransportError;
+
 // TODO https://lib.rs/crates/partial-enum
 // Return subset of errors, that match exactly what the API can return
 
@@ -22,7 +24,24 @@ pub enum Error {
     BadSentence(String),
     BadTranslation(String),
     DeserializeError(std::io::Error),
-    Transport(ureq::Transport),
+    /// A request body or an [`AccountArchive`](crate::archive::AccountArchive) couldn't be
+    /// serialized to JSON
+    SerializeError(serde_json::Error),
+    /// The response was valid JSON, but didn't have the expected shape
+    UnexpectedResponse(String),
+    /// No response could be obtained, see [`Transport`](crate::transport::Transport)
+    Transport(TransportError),
+    /// The request was rejected before being sent, because the field it names would have made
+    /// jpdb reject it too
+    InvalidRequest(&'static str, String),
+    /// A batch sent in chunks failed part way, after the listed words were applied
+    PartialBatch(Vec<crate::request::Vocabulary>, Box<Error>),
+    /// An [`AccountArchive`](crate::archive::AccountArchive) was written by a newer version of
+    /// this crate, or an [`ImportLog`](crate::archive::ImportLog) belongs to another archive
+    BadArchive(String),
+    /// A field that wasn't requested is needed to make sense of the response, eg. to render
+    /// furigana
+    MissingField(&'static str, String),
     Unhandled(u16, RawError),
 }
 
@@ -46,13 +65,19 @@ pub enum ErrorKind {
     TooManyCardsTotal,
     Transport,
     DeserializeError,
+    SerializeError,
+    UnexpectedResponse,
+    InvalidRequest,
+    PartialBatch,
+    BadArchive,
+    MissingField,
     Unhandled,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct RawError {
-    error_message: String,
-    error: String,
+    pub error_message: String,
+    pub error: String,
 }
 
 impl Error {
@@ -77,6 +102,12 @@ impl Error {
             Error::BadSentence(_) => ErrorKind::BadSentence,
             Error::BadTranslation(_) => ErrorKind::BadTranslation,
             Error::DeserializeError(_) => ErrorKind::DeserializeError,
+            Error::SerializeError(_) => ErrorKind::SerializeError,
+            Error::UnexpectedResponse(_) => ErrorKind::UnexpectedResponse,
+            Error::InvalidRequest(_, _) => ErrorKind::InvalidRequest,
+            Error::PartialBatch(_, _) => ErrorKind::PartialBatch,
+            Error::BadArchive(_) => ErrorKind::BadArchive,
+            Error::MissingField(_, _) => ErrorKind::MissingField,
         }
     }
 }
@@ -84,8 +115,10 @@ impl Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
-            Error::Transport(ref source) => Some(source),
+            Error::Transport(ref source) => Some(source.as_ref()),
             Error::DeserializeError(ref source) => Some(source),
+            Error::SerializeError(ref source) => Some(source),
+            Error::PartialBatch(_, ref source) => Some(source.as_ref()),
             _ => None,
         }
     }
@@ -120,54 +153,73 @@ impl std::fmt::Display for Error {
             ),
             Error::BadTranslation(ref s) => write!(f, "The translation is too long. {s}"),
             Error::DeserializeError(ref s) => s.fmt(f),
+            Error::SerializeError(ref e) => write!(f, "Couldn't serialize to JSON. {e}"),
+            Error::UnexpectedResponse(ref s) => {
+                write!(f, "The API returned an unexpected response. {s}")
+            }
+            Error::InvalidRequest(field, ref s) => write!(f, "Invalid field \"{field}\". {s}"),
+            Error::PartialBatch(ref applied, ref e) => {
+                write!(f, "Only {} words were applied. {e}", applied.len())
+            }
+            Error::BadArchive(ref s) => write!(f, "The archive can't be imported. {s}"),
+            Error::MissingField(field, ref s) => {
+                write!(f, "Field \"{field}\" wasn't requested. {s}")
+            }
         }
     }
 }
 
-impl From<ureq::Error> for Error {
-    fn from(r: ureq::Error) -> Self {
-        match r {
-            ureq::Error::Status(code, response) => {
-                //TODO improve this
-                let raw = response
-                    .into_json::<RawError>()
-                    .map_err(Error::DeserializeError);
-                if let Err(e) = raw {
-                    return e;
-                }
-                let raw = raw.unwrap();
+impl Error {
+    /// Maps the body of an error response from jpdb to the matching variant.
+    ///
+    /// Bodies that aren't a jpdb error, like the HTML page of a proxy, are kept as the message.
+    pub(crate) fn from_response(code: u16, body: &[u8]) -> Self {
+        let raw = serde_json::from_slice::<RawError>(body).unwrap_or_else(|_| RawError {
+            error_message: String::from_utf8_lossy(body).trim().to_owned(),
+            error: String::new(),
+        });
+        Error::from_raw(code, raw)
+    }
 
-                match code {
-                    403 => {
-                        if raw.error != "bad_key" {
-                            Error::Unhandled(code, raw)
-                        } else {
-                            match raw.error_message.as_str() {
-                                "missing API key" => Error::MissingKey(raw.error_message),
-                                "invalid API key" => Error::BadKey(raw.error_message),
-                                _ => Error::Unhandled(code, raw),
-                            }
-                        }
-                    }
-                    429 => {
-                        if raw.error != "too_many_requests" {
-                            Error::Unhandled(code, raw)
-                        } else {
-                            //TODO uhh wait until 429 is implemented to match the string properly
-                            Error::TooManyRequests(raw.error_message)
-                        }
-                    }
-                    400 => match raw.error.as_str() {
-                        "bad_request" => Error::BadRequest(raw.error_message),
-                        "bad_deck" => Error::BadDeck(raw.error_message),
-                        "bad_sid" => Error::BadSid(raw.error_message),
-                        "bad_vid" => Error::BadVid(raw.error_message),
-                        _ => Error::Unhandled(code, raw),
+    /// Maps an error response from jpdb to the matching variant, from its `error` field first,
+    /// then from its status code for the errors jpdb didn't describe
+    pub(crate) fn from_raw(code: u16, raw: RawError) -> Self {
+        let message = raw.error_message;
+        match raw.error.as_str() {
+            "bad_key" if message == "missing API key" => Error::MissingKey(message),
+            "bad_key" => Error::BadKey(message),
+            "too_many_requests" => Error::TooManyRequests(message),
+            "api_unavailable" => Error::ApiUnavailable(message),
+            "bad_request" => Error::BadRequest(message),
+            "too_many_decks" => Error::TooManyDecks(message),
+            "too_many_cards_in_deck" => Error::TooManyCardsInDeck(message),
+            "too_many_cards_total" => Error::TooManyCardsTotal(message),
+            "bad_deck" => Error::BadDeck(message),
+            "bad_vid" => Error::BadVid(message),
+            "bad_sid" => Error::BadSid(message),
+            "bad_rid" => Error::BadRid(message),
+            "bad_image" => Error::BadImage(message),
+            "bad_audio" => Error::BadAudio(message),
+            "bad_sentence" => Error::BadSentence(message),
+            "bad_translation" => Error::BadTranslation(message),
+            _ => match code {
+                429 => Error::TooManyRequests(message),
+                500..=599 => Error::ApiUnavailable(message),
+                _ => Error::Unhandled(
+                    code,
+                    RawError {
+                        error_message: message,
+                        error: raw.error,
                     },
-                    _ => Error::Unhandled(code, raw),
-                }
-            }
-            ureq::Error::Transport(a) => Error::Transport(a),
+                ),
+            },
         }
     }
 }
+
+#[cfg(feature = "async")]
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Transport(Box::new(e))
+    }
+}
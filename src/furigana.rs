@@ -0,0 +1,163 @@
+//! Renders the furigana of a parsed text, in the formats readers and flashcard apps expect.
+//!
+//! The text has to be parsed with at least the `PositionUtf8`, `LengthUtf8` and `Furigana`
+//! [`TokenQueryField`](crate::request::TokenQueryField)s, and with the `CardState`
+//! [`VocabQueryField`](crate::request::VocabQueryField) too when [`FuriganaOptions::skip_known`]
+//! is set. Rendering fails with [`Error::MissingField`] otherwise.
+//!
+//! ```no_run
+//! use jpdb::{client::Client, furigana::{self, FuriganaOptions}, request::TokenQueryField};
+//!
+//! let client = Client::new("my_jpdb_token");
+//! let text = "死神の本";
+//! let parsed = client.parse_text(
+//!     text,
+//!     &[TokenQueryField::PositionUtf8, TokenQueryField::LengthUtf8, TokenQueryField::Furigana],
+//!     None,
+//! )?;
+//! // 死神[しにがみ]の 本[ほん]
+//! println!("{}", furigana::render_anki(text, &parsed, FuriganaOptions::default())?);
+//! # Ok::<(), jpdb::error::Error>(())
+//! ```
+
+use crate::{
+    card::CardState,
+    error::Error,
+    request::{Furigana, ParseResult, Token},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FuriganaOptions {
+    /// Leaves the words whose card is known, or in the never-forget deck, without furigana
+    pub skip_known: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Html,
+    Anki,
+    Reading,
+}
+
+/// `<ruby>死神<rt>しにがみ</rt></ruby>の`, with the rest of the text escaped
+pub fn render_html(
+    text: &str,
+    parsed: &ParseResult,
+    options: FuriganaOptions,
+) -> Result<String, Error> {
+    render(text, parsed, options, Style::Html)
+}
+
+/// `死神[しにがみ]の`, as understood by Anki's `furigana:` filter. A space is inserted before
+/// every annotated part that doesn't start the text, since Anki uses it to know where it begins.
+pub fn render_anki(
+    text: &str,
+    parsed: &ParseResult,
+    options: FuriganaOptions,
+) -> Result<String, Error> {
+    render(text, parsed, options, Style::Anki)
+}
+
+/// `しにがみの`, the text with every annotated part replaced by its reading
+pub fn render_reading(
+    text: &str,
+    parsed: &ParseResult,
+    options: FuriganaOptions,
+) -> Result<String, Error> {
+    render(text, parsed, options, Style::Reading)
+}
+
+fn push_plain(out: &mut String, plain: &str, style: Style) {
+    if style != Style::Html {
+        out.push_str(plain);
+        return;
+    }
+    push_escaped(out, plain);
+}
+
+/// Pushes `text` escaped for HTML and XML, both inside elements and quoted attributes
+pub(crate) fn push_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn push_ruby(out: &mut String, base: &str, reading: &str, style: Style) {
+    match style {
+        Style::Html => {
+            out.push_str("<ruby>");
+            push_plain(out, base, style);
+            out.push_str("<rt>");
+            push_plain(out, reading, style);
+            out.push_str("</rt></ruby>");
+        }
+        Style::Anki => {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(base);
+            out.push('[');
+            out.push_str(reading);
+            out.push(']');
+        }
+        Style::Reading => out.push_str(reading),
+    }
+}
+
+fn is_known(parsed: &ParseResult, token: &Token) -> bool {
+    token
+        .vocabulary_index
+        .and_then(|index| parsed.vocabulary.get(index))
+        .and_then(|entry| entry.card_state.as_ref())
+        .is_some_and(|states| states.iter().any(CardState::is_known))
+}
+
+fn missing_field(name: &'static str) -> Error {
+    Error::MissingField(name, String::from("needed to render furigana"))
+}
+
+fn render(
+    text: &str,
+    parsed: &ParseResult,
+    options: FuriganaOptions,
+    style: Style,
+) -> Result<String, Error> {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut cursor = 0;
+    for token in &parsed.tokens {
+        let start = token
+            .position_utf8
+            .ok_or_else(|| missing_field("position_utf8"))?;
+        let length = token
+            .length_utf8
+            .ok_or_else(|| missing_field("length_utf8"))?;
+        let end = start + length;
+        // Overlapping tokens, or ones that don't belong to this text, are left as they are
+        let (Some(before), Some(surface)) = (text.get(cursor..start), text.get(start..end)) else {
+            continue;
+        };
+        push_plain(&mut out, before, style);
+        cursor = end;
+        let furigana = match token.furigana {
+            Some(ref furigana) if !(options.skip_known && is_known(parsed, token)) => furigana,
+            _ => {
+                push_plain(&mut out, surface, style);
+                continue;
+            }
+        };
+        for part in furigana {
+            match part {
+                Furigana::Plain(plain) => push_plain(&mut out, plain, style),
+                Furigana::Ruby(base, reading) => push_ruby(&mut out, base, reading, style),
+            }
+        }
+    }
+    push_plain(&mut out, text.get(cursor..).unwrap_or_default(), style);
+    Ok(out)
+}
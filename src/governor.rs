@@ -0,0 +1,147 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::error::{Error, ErrorKind};
+
+/// How many requests a [`Client`](crate::client::Client) may send, enforced with a token bucket.
+///
+/// Up to `burst` requests can be sent at once, after which the client waits so that no more than
+/// `requests` are sent every `per`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// `requests` every `per`, with a burst of the same size
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self {
+            requests,
+            per,
+            burst: requests,
+        }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Shared by every clone of a client, so that they all draw from the same bucket
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(limit.burst.max(1)),
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token if there is one, or tells how long to wait for the next one
+    fn try_acquire(&self) -> Result<(), Duration> {
+        let rate = f64::from(self.limit.requests.max(1)) / self.limit.per.as_secs_f64();
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.last_refill).as_secs_f64() * rate;
+        bucket.tokens = (bucket.tokens + refill).min(f64::from(self.limit.burst.max(1)));
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+    }
+
+    /// Blocks until a request may be sent
+    pub(crate) fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Waits until a request may be sent, without blocking the thread
+    #[cfg(feature = "async")]
+    pub(crate) async fn acquire_async(&self) {
+        while let Err(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// When and how a [`Client`](crate::client::Client) retries a failed request.
+///
+/// Requests failing with [`ErrorKind::TooManyRequests`] are retried up to `max_retries` times.
+/// Those failing with [`ErrorKind::ApiUnavailable`] or [`ErrorKind::Transport`] are too, when
+/// they're [idempotent](crate::endpoint::Endpoint::is_idempotent). The client waits for as long as
+/// the `Retry-After` header asks when it's present, up to `max_backoff`, and backs off
+/// exponentially otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Requests that aren't idempotent are only retried when jpdb turned them away, as otherwise
+    /// they may have been applied already
+    pub(crate) fn should_retry(&self, error: &Error, attempt: u32, idempotent: bool) -> bool {
+        attempt < self.max_retries
+            && match error.kind() {
+                ErrorKind::TooManyRequests => true,
+                ErrorKind::ApiUnavailable | ErrorKind::Transport => idempotent,
+                _ => false,
+            }
+    }
+
+    /// How long to wait before the retry following `attempt`: as long as the `Retry-After` header
+    /// asks if there is one, up to `max_backoff`
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after
+            .map(|delay| delay.min(self.max_backoff))
+            .unwrap_or_else(|| self.backoff(attempt))
+    }
+
+    /// How long to wait before the retry following `attempt`, which starts at 0
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        Duration::try_from_secs_f64(self.initial_backoff.as_secs_f64() * factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
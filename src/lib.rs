@@ -1,7 +1,21 @@
-#![feature(let_else)]
-
+pub mod api_key;
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod batch;
+pub mod card;
 pub mod client;
+pub mod deck_ops;
+pub mod endpoint;
+pub mod furigana;
+pub mod governor;
+mod parse;
+pub mod pitch;
 pub mod request;
+pub mod sync;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transport;
 
 pub mod error;
 #[cfg(test)]
@@ -0,0 +1,290 @@
+//! Helpers to parse texts across several requests, and merge the results back together.
+
+use std::{collections::HashMap, ops::Range};
+
+#[cfg(feature = "async")]
+use crate::async_client::AsyncClient;
+use crate::{
+    client::Client,
+    endpoint::ParseTexts,
+    error::Error,
+    request::{
+        dedup_fields, ParseResult, ParseTextsResult, Sid, Token, TokenQueryField, Vid,
+        VocabQueryField, VocabularyEntry,
+    },
+};
+
+/// Paragraphs are preferred as split points, since they never cut a sentence in half
+fn is_paragraph_end(c: char) -> bool {
+    c == '\n'
+}
+
+/// Closing quotes are included, so that `「はい。」` isn't split before its `」`
+fn is_sentence_end(c: char) -> bool {
+    matches!(
+        c,
+        '。' | '！' | '？' | '!' | '?' | '.' | '．' | '…' | '」' | '』' | '）' | ')'
+    )
+}
+
+/// Byte index right after the last character of `window` matching `pred`
+fn last_boundary(window: &str, pred: fn(char) -> bool) -> Option<usize> {
+    window
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| pred(c))
+        .map(|(i, c)| i + c.len_utf8())
+}
+
+/// A piece of a longer text, with its position in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Piece {
+    pub(crate) range: Range<usize>,
+    /// Position of the piece in characters, for [`TokenQueryField::PositionUtf32`]
+    pub(crate) start_utf32: usize,
+}
+
+/// Splits `text` in pieces of at most `budget` bytes, cutting after the last paragraph or sentence
+/// that fits, or anywhere between two characters if there is neither.
+pub(crate) fn split_text(text: &str, budget: usize) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut start_utf32 = 0;
+    while text.len() - start > budget {
+        let mut end = start + budget;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let window = &text[start..end];
+        let cut = last_boundary(window, is_paragraph_end)
+            .or_else(|| last_boundary(window, is_sentence_end))
+            .unwrap_or(window.len());
+        // A single character larger than the budget still has to be sent
+        let cut = match cut {
+            0 => text[start..].chars().next().map_or(0, char::len_utf8),
+            cut => cut,
+        };
+        pieces.push(Piece {
+            range: start..start + cut,
+            start_utf32,
+        });
+        start_utf32 += text[start..start + cut].chars().count();
+        start += cut;
+    }
+    if start < text.len() || pieces.is_empty() {
+        pieces.push(Piece {
+            range: start..text.len(),
+            start_utf32,
+        });
+    }
+    pieces
+}
+
+/// Groups consecutive texts in batches of at most `budget` bytes. A text larger than the budget
+/// is sent on its own.
+pub(crate) fn pack_texts(texts: &[&str], budget: usize) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+    for (i, text) in texts.iter().enumerate() {
+        if i > start && size + text.len() > budget {
+            batches.push(start..i);
+            start = i;
+            size = 0;
+        }
+        size += text.len();
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+    batches
+}
+
+/// Adds `required` to `fields` if it's missing
+pub(crate) fn with_field<F: Clone + PartialEq>(fields: &[F], required: F) -> Vec<F> {
+    let mut fields = fields.to_vec();
+    if !fields.contains(&required) {
+        fields.push(required);
+    }
+    fields
+}
+
+/// The fields needed to merge several parse results, on top of the ones that were asked for
+pub(crate) fn merge_fields(
+    token_fields: &[TokenQueryField],
+    vocab_fields: &[VocabQueryField],
+) -> (Vec<TokenQueryField>, Vec<VocabQueryField>) {
+    let token_fields = with_field(token_fields, TokenQueryField::VocabIndex);
+    let vocab_fields = with_field(vocab_fields, VocabQueryField::Vid);
+    let vocab_fields = with_field(&vocab_fields, VocabQueryField::Sid);
+    (token_fields, vocab_fields)
+}
+
+/// Merges parse results into a single vocabulary table, deduplicated by vid and sid
+#[derive(Debug, Default)]
+pub(crate) struct Merger {
+    vocabulary: Vec<VocabularyEntry>,
+    indices: HashMap<(Option<Vid>, Option<Sid>), usize>,
+}
+
+impl Merger {
+    /// Adds `vocabulary` to the table, and returns where each of its entries ended up in it
+    pub(crate) fn add_vocabulary(&mut self, vocabulary: Vec<VocabularyEntry>) -> Vec<usize> {
+        vocabulary
+            .into_iter()
+            .map(|entry| {
+                *self
+                    .indices
+                    .entry((entry.vid, entry.sid))
+                    .or_insert_with(|| {
+                        self.vocabulary.push(entry);
+                        self.vocabulary.len() - 1
+                    })
+            })
+            .collect()
+    }
+
+    /// Points the vocabulary index of `tokens` into the table according to `mapping`, and moves
+    /// their positions by the given offsets
+    pub(crate) fn remap(
+        tokens: Vec<Token>,
+        mapping: &[usize],
+        offset_utf8: usize,
+        offset_utf32: usize,
+    ) -> Result<Vec<Token>, Error> {
+        tokens
+            .into_iter()
+            .map(|mut token| {
+                if let Some(index) = token.vocabulary_index {
+                    let merged = mapping.get(index).ok_or_else(|| {
+                        Error::UnexpectedResponse(format!(
+                            "token points to vocabulary {index}, but there are only {}",
+                            mapping.len()
+                        ))
+                    })?;
+                    token.vocabulary_index = Some(*merged);
+                }
+                token.position_utf8 = token.position_utf8.map(|p| p + offset_utf8);
+                token.position_utf32 = token.position_utf32.map(|p| p + offset_utf32);
+                Ok(token)
+            })
+            .collect()
+    }
+
+    /// Adds the vocabulary of `result` to the table, and returns its remapped tokens
+    pub(crate) fn merge(
+        &mut self,
+        result: ParseResult,
+        offset_utf8: usize,
+        offset_utf32: usize,
+    ) -> Result<Vec<Token>, Error> {
+        let mapping = self.add_vocabulary(result.vocabulary);
+        Self::remap(result.tokens, &mapping, offset_utf8, offset_utf32)
+    }
+
+    /// Adds the vocabulary of a batch to the table, and returns the remapped tokens of each text
+    pub(crate) fn merge_batch(
+        &mut self,
+        result: ParseTextsResult,
+    ) -> Result<Vec<Vec<Token>>, Error> {
+        let mapping = self.add_vocabulary(result.vocabulary);
+        result
+            .tokens
+            .into_iter()
+            .map(|tokens| Self::remap(tokens, &mapping, 0, 0))
+            .collect()
+    }
+
+    pub(crate) fn into_vocabulary(self) -> Vec<VocabularyEntry> {
+        self.vocabulary
+    }
+}
+
+/// Implements the calls split over several requests for a client, with `async await` for
+/// [`AsyncClient`], so that both clients merge the results the same way
+macro_rules! impl_parse {
+    ($client:ty $(, $async:tt $await:tt)?) => {
+        impl $client {
+            /// Parses a text of any length, by splitting it in pieces of at most `budget` bytes, on
+            /// paragraph or sentence boundaries when possible.
+            ///
+            /// The positions of the tokens are relative to `text`, and the vocabulary is
+            /// deduplicated across pieces. [`TokenQueryField::VocabIndex`],
+            /// [`VocabQueryField::Vid`] and [`VocabQueryField::Sid`] are always requested, since
+            /// the pieces can't be merged without them.
+            pub $($async)? fn parse_long_text(
+                &self,
+                text: &str,
+                token_fields: &[TokenQueryField],
+                vocab_fields: Option<&[VocabQueryField]>,
+                budget: usize,
+            ) -> Result<ParseResult, Error> {
+                let (token_fields, vocab_fields) =
+                    merge_fields(token_fields, vocab_fields.unwrap_or_default());
+                let mut merger = Merger::default();
+                let mut tokens = Vec::new();
+                for piece in split_text(text, budget) {
+                    let result = self
+                        .parse_text(
+                            &text[piece.range.clone()],
+                            &token_fields,
+                            Some(&vocab_fields),
+                        )
+                        $(.$await)? ?;
+                    tokens.extend(merger.merge(result, piece.range.start, piece.start_utf32)?);
+                }
+                Ok(ParseResult {
+                    tokens,
+                    vocabulary: merger.into_vocabulary(),
+                })
+            }
+
+            /// Parses many independent texts, sending as many of them per request as fit in
+            /// `budget` bytes, like with [`Client::parse_long_text`].
+            ///
+            /// [`ParseTextsResult::tokens`] has one list per text, in the same order, and the
+            /// vocabulary is shared by all of them. [`TokenQueryField::VocabIndex`],
+            /// [`VocabQueryField::Vid`] and [`VocabQueryField::Sid`] are always requested.
+            /// Texts aren't split, one that is larger than `budget` is sent on its own.
+            pub $($async)? fn parse_texts(
+                &self,
+                texts: &[&str],
+                token_fields: &[TokenQueryField],
+                vocab_fields: Option<&[VocabQueryField]>,
+                budget: usize,
+            ) -> Result<ParseTextsResult, Error> {
+                let (token_fields, vocab_fields) =
+                    merge_fields(token_fields, vocab_fields.unwrap_or_default());
+                let token_fields = dedup_fields(&token_fields);
+                let vocab_fields = dedup_fields(&vocab_fields);
+                let mut merger = Merger::default();
+                let mut tokens = Vec::with_capacity(texts.len());
+                for batch in pack_texts(texts, budget) {
+                    let chunk = &texts[batch];
+                    let response = self
+                        .execute(&ParseTexts {
+                            text: chunk,
+                            token_fields: &token_fields,
+                            vocabulary_fields: &vocab_fields,
+                        })
+                        $(.$await)? ?;
+                    let result = ParseTextsResult::from_response(
+                        chunk.len(),
+                        &token_fields,
+                        &vocab_fields,
+                        response,
+                    )?;
+                    tokens.extend(merger.merge_batch(result)?);
+                }
+                Ok(ParseTextsResult {
+                    tokens,
+                    vocabulary: merger.into_vocabulary(),
+                })
+            }
+        }
+    };
+}
+
+impl_parse!(Client);
+#[cfg(feature = "async")]
+impl_parse!(AsyncClient, async await);
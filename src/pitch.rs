@@ -0,0 +1,208 @@
+//! Decodes and renders the pitch accent of a word, as returned for
+//! [`VocabQueryField::PitchAccent`](crate::request::VocabQueryField::PitchAccent).
+//!
+//! jpdb sends one pattern per reading, with a `H` or `L` per mora, eg. `LHHL` for a word of three
+//! morae whose pitch drops on the particle following it.
+//!
+//! [`PitchAccent::to_html`] only emits classes, which can be styled with eg.
+//!
+//! ```css
+//! .pitch-high { text-decoration: overline; }
+//! .pitch-drop { border-right: 1px solid; }
+//! ```
+
+use crate::{error::Error, furigana, request::VocabularyEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pitch {
+    High,
+    Low,
+}
+
+/// The pitch of every mora of a reading, and possibly of the particle following it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PitchAccent {
+    pub morae: Vec<String>,
+    /// Either as long as `morae`, or one longer when the pattern includes the following particle
+    pub pitches: Vec<Pitch>,
+}
+
+/// Small kana are pronounced along with the kana before them, while `ー`, `っ` and `ん` are morae
+/// of their own
+fn is_small_kana(c: char) -> bool {
+    matches!(
+        c,
+        'ゃ' | 'ゅ'
+            | 'ょ'
+            | 'ぁ'
+            | 'ぃ'
+            | 'ぅ'
+            | 'ぇ'
+            | 'ぉ'
+            | 'ゎ'
+            | 'ャ'
+            | 'ュ'
+            | 'ョ'
+            | 'ァ'
+            | 'ィ'
+            | 'ゥ'
+            | 'ェ'
+            | 'ォ'
+            | 'ヮ'
+    )
+}
+
+/// Splits a reading in morae, eg. `きょうと` in `きょ`, `う`, `と`
+pub fn split_morae(reading: &str) -> Vec<&str> {
+    let mut morae: Vec<&str> = Vec::new();
+    let mut start = 0;
+    for (index, c) in reading.char_indices() {
+        if index == 0 || is_small_kana(c) {
+            continue;
+        }
+        morae.push(&reading[start..index]);
+        start = index;
+    }
+    if start < reading.len() {
+        morae.push(&reading[start..]);
+    }
+    morae
+}
+
+impl PitchAccent {
+    /// Fails with [`Error::UnexpectedResponse`] if the pattern isn't made of `H` and `L`, or doesn't
+    /// match the number of morae
+    pub fn new(reading: &str, pattern: &str) -> Result<Self, Error> {
+        let morae: Vec<String> = split_morae(reading).into_iter().map(String::from).collect();
+        let pitches = pattern
+            .chars()
+            .map(|c| match c {
+                'H' => Ok(Pitch::High),
+                'L' => Ok(Pitch::Low),
+                other => Err(Error::UnexpectedResponse(format!(
+                    "unknown pitch \"{other}\" in pattern \"{pattern}\""
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if pitches.len() != morae.len() && pitches.len() != morae.len() + 1 {
+            return Err(Error::UnexpectedResponse(format!(
+                "pattern \"{pattern}\" doesn't fit the {} morae of \"{reading}\"",
+                morae.len()
+            )));
+        }
+        Ok(Self { morae, pitches })
+    }
+
+    /// The mora after which the pitch drops, counting from 1, or 0 if it never drops.
+    ///
+    /// A pattern that doesn't include the following particle can't tell whether the pitch drops
+    /// after the last mora, and is considered flat.
+    pub fn downstep(&self) -> usize {
+        self.pitches
+            .windows(2)
+            .position(|w| w == [Pitch::High, Pitch::Low])
+            .map_or(0, |i| i + 1)
+    }
+
+    fn drops_after(&self, index: usize) -> bool {
+        self.pitches.get(index) == Some(&Pitch::High)
+            && self.pitches.get(index + 1) == Some(&Pitch::Low)
+    }
+
+    /// `<span class="pitch-low">き</span><span class="pitch-high pitch-drop">ょう</span>...`, one
+    /// span per run of morae of the same pitch
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let mut index = 0;
+        while index < self.morae.len() {
+            let pitch = self.pitches[index];
+            let mut end = index + 1;
+            while end < self.morae.len() && self.pitches[end] == pitch && !self.drops_after(end - 1)
+            {
+                end += 1;
+            }
+            let class = match pitch {
+                Pitch::High if self.drops_after(end - 1) => "pitch-high pitch-drop",
+                Pitch::High => "pitch-high",
+                Pitch::Low => "pitch-low",
+            };
+            html.push_str(&format!("<span class=\"{class}\">"));
+            furigana::push_escaped(&mut html, &self.morae[index..end].concat());
+            html.push_str("</span>");
+            index = end;
+        }
+        html
+    }
+
+    /// A graph of the pitch, with a dot per mora linked by lines and the morae written below,
+    /// like the ones on jpdb. The particle, if the pattern includes it, is drawn as a hollow dot.
+    pub fn to_svg(&self) -> String {
+        const STEP: usize = 24;
+        const HIGH: usize = 8;
+        const LOW: usize = 28;
+        const TEXT: usize = 52;
+        let x = |index: usize| STEP / 2 + index * STEP;
+        let y = |pitch: Pitch| match pitch {
+            Pitch::High => HIGH,
+            Pitch::Low => LOW,
+        };
+
+        let width = self.pitches.len() * STEP;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"60\" viewBox=\"0 0 {width} 60\">"
+        );
+        let points: Vec<String> = self
+            .pitches
+            .iter()
+            .enumerate()
+            .map(|(i, &pitch)| format!("{},{}", x(i), y(pitch)))
+            .collect();
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"currentColor\" stroke-width=\"2\"/>",
+            points.join(" ")
+        ));
+        for (i, &pitch) in self.pitches.iter().enumerate() {
+            let fill = if i < self.morae.len() {
+                "currentColor"
+            } else {
+                "none"
+            };
+            svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"4\" fill=\"{fill}\" stroke=\"currentColor\" stroke-width=\"2\"/>",
+                x(i),
+                y(pitch)
+            ));
+        }
+        for (i, mora) in self.morae.iter().enumerate() {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{TEXT}\" text-anchor=\"middle\" font-size=\"14\" fill=\"currentColor\">",
+                x(i)
+            ));
+            furigana::push_escaped(&mut svg, mora);
+            svg.push_str("</text>");
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+impl VocabularyEntry {
+    /// Decodes [`VocabularyEntry::pitch_accent`] against [`VocabularyEntry::reading`], which has
+    /// to be requested too, or this fails with [`Error::MissingField`]. Empty if the pitch accent
+    /// wasn't requested, or is unknown.
+    pub fn pitch_accents(&self) -> Result<Vec<PitchAccent>, Error> {
+        let Some(ref patterns) = self.pitch_accent else {
+            return Ok(Vec::new());
+        };
+        let Some(ref reading) = self.reading else {
+            return Err(Error::MissingField(
+                "reading",
+                String::from("needed to decode the pitch accent"),
+            ));
+        };
+        patterns
+            .iter()
+            .map(|pattern| PitchAccent::new(reading, pattern))
+            .collect()
+    }
+}
@@ -1,24 +1,31 @@
 use std::collections::HashMap;
 
-use serde::{Deserialize, Serialize, Serializer};
-use serde_json::json;
-
-use crate::{client::Client, error::Error};
-
-/// A request ready to be sent, users of the crate won't have to touch anything in here normally.
-#[derive(Debug)]
-pub(crate) struct Request {
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    card::{CardState, DueAt},
+    client::Client,
+    endpoint::{self, Endpoint},
+    error::Error,
+};
+
+/// A request ready to be sent, users of the crate won't have to touch anything in here normally,
+/// unless they implement their own [`Transport`](crate::transport::Transport).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
     /// The full URL that the request will be sent to, this includes the base URL and the API endpoint
     pub url: String,
     /// The body of the request
     pub body: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum AnyDeckWidget {
     UserDeckId(u8),
     Blacklist,
     NeverForget,
+    /// A special deck this crate doesn't know about yet
+    Unknown(String),
 }
 
 // #[derive(serde::Serialize)]
@@ -26,6 +33,18 @@ pub trait AnyDeckId {
     fn as_any(&self) -> AnyDeckWidget;
 }
 
+impl AnyDeckId for AnyDeckWidget {
+    fn as_any(&self) -> AnyDeckWidget {
+        self.clone()
+    }
+}
+
+impl<T: AnyDeckId + ?Sized> AnyDeckId for &T {
+    fn as_any(&self) -> AnyDeckWidget {
+        (**self).as_any()
+    }
+}
+
 impl Serialize for AnyDeckWidget {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -35,17 +54,18 @@ impl Serialize for AnyDeckWidget {
             AnyDeckWidget::UserDeckId(x) => serializer.serialize_u8(x),
             AnyDeckWidget::NeverForget => serializer.serialize_str("never-forget"),
             AnyDeckWidget::Blacklist => serializer.serialize_str("blacklist"),
+            AnyDeckWidget::Unknown(ref s) => serializer.serialize_str(s),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[serde(from = "u32")]
 pub struct Vid(pub u32);
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[serde(from = "u32")]
 pub struct Rid(pub u32);
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[serde(from = "u32")]
 pub struct Sid(pub u32);
 
@@ -67,6 +87,30 @@ impl From<u32> for Rid {
     }
 }
 
+impl<'de> Deserialize<'de> for AnyDeckWidget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawDeckId {
+            User(u8),
+            Special(String),
+        }
+
+        let special = match RawDeckId::deserialize(deserializer)? {
+            RawDeckId::User(x) => return Ok(AnyDeckWidget::UserDeckId(x)),
+            RawDeckId::Special(s) => s,
+        };
+        Ok(match special.as_str() {
+            "never-forget" => AnyDeckWidget::NeverForget,
+            "blacklist" => AnyDeckWidget::Blacklist,
+            _ => AnyDeckWidget::Unknown(special),
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
 pub struct Vocabulary(pub u32, pub u32);
 
@@ -95,6 +139,7 @@ impl From<(Vid, Sid)> for Vocabulary {
 }
 
 //TODO option this in functions? setcardsentence takes vocab instead
+//TODO attach audio and images, once jpdb documents how they have to be uploaded
 #[derive(Serialize, Debug, Clone, Copy, Eq, PartialEq)]
 pub struct SetCardSentenceOptions<'a> {
     pub vid: Vid,
@@ -163,7 +208,7 @@ pub struct DeckVocabulary {
 }
 
 #[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
-struct CreateEmptyDeckResponse {
+pub struct CreateEmptyDeckResponse {
     id: u8,
 }
 
@@ -191,7 +236,7 @@ pub enum DeckQueryField {
     IsBuiltIn,
 }
 
-#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum VocabQueryField {
     #[serde(rename = "vid")]
     Vid,
@@ -213,6 +258,33 @@ pub enum VocabQueryField {
     CardState,
     #[serde(rename = "due_at")]
     DueAt,
+    #[serde(rename = "part_of_speech")]
+    PartOfSpeech,
+    /// The glosses of every meaning, see [`VocabularyEntry::meaning_chunks`]
+    #[serde(rename = "meanings_chunks")]
+    MeaningsChunks,
+    /// The part of speech of every meaning, see [`VocabularyEntry::meaning_chunks`]
+    #[serde(rename = "meanings_part_of_speech")]
+    MeaningsPartOfSpeech,
+    #[serde(rename = "alt_sids")]
+    AltSids,
+    #[serde(rename = "alt_spellings")]
+    AltSpellings,
+    #[serde(rename = "pitch_accent")]
+    PitchAccent,
+    /// A field this crate doesn't know about yet, its raw value ends up in
+    /// [`VocabularyEntry::other`]. Names of known fields are decoded into their own field, eg.
+    /// `Other("vid")` sets [`VocabularyEntry::vid`].
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl VocabQueryField {
+    /// The field jpdb calls `name`, [`VocabQueryField::Other`] if this crate doesn't know about it
+    pub fn from_name(name: &str) -> Self {
+        serde_json::from_value(serde_json::Value::from(name))
+            .unwrap_or_else(|_| Self::Other(name.to_owned()))
+    }
 }
 
 //TODO remove utf32?
@@ -232,6 +304,353 @@ pub enum TokenQueryField {
     Furigana,
 }
 
+/// A piece of a token's furigana, as returned by jpdb when [`TokenQueryField::Furigana`] is requested.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Furigana {
+    /// Text that doesn't need a reading, usually kana
+    Plain(String),
+    /// Kanji, along with its reading
+    Ruby(String, String),
+}
+
+/// A token of a parsed text. Only the fields that were requested are populated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Token {
+    /// Index of this token's vocabulary in [`ParseResult::vocabulary`]
+    pub vocabulary_index: Option<usize>,
+    pub position_utf8: Option<usize>,
+    pub position_utf32: Option<usize>,
+    pub length_utf8: Option<usize>,
+    pub length_utf32: Option<usize>,
+    /// `None` if the furigana wasn't requested, or if the token doesn't need any
+    pub furigana: Option<Vec<Furigana>>,
+}
+
+/// A vocabulary entry returned by the API. Only the fields that were requested are populated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VocabularyEntry {
+    pub vid: Option<Vid>,
+    pub sid: Option<Sid>,
+    pub rid: Option<Rid>,
+    pub spelling: Option<String>,
+    pub reading: Option<String>,
+    /// `None` if the frequency rank wasn't requested, or if the word doesn't have one
+    pub frequency_rank: Option<u32>,
+    pub meanings: Option<Vec<String>>,
+    /// `None` if the card level wasn't requested, or if the word isn't in any deck
+    pub card_level: Option<u32>,
+    /// `None` if the card state wasn't requested, or if the word isn't in any deck
+    pub card_state: Option<Vec<CardState>>,
+    /// `None` if it wasn't requested, or if the card isn't due
+    pub due_at: Option<DueAt>,
+    pub part_of_speech: Option<Vec<String>>,
+    /// Populated by [`VocabQueryField::MeaningsChunks`] and
+    /// [`VocabQueryField::MeaningsPartOfSpeech`], each filling its half of every meaning
+    pub meaning_chunks: Option<Vec<Meaning>>,
+    /// The other spellings of the word
+    pub alt_sids: Option<Vec<Sid>>,
+    pub alt_spellings: Option<Vec<String>>,
+    /// One pattern per reading of the word, eg. `LHHH`, decoded by
+    /// [`VocabularyEntry::pitch_accents`]
+    pub pitch_accent: Option<Vec<String>>,
+    /// Raw values of the [`VocabQueryField::Other`] fields, by name
+    pub other: HashMap<String, serde_json::Value>,
+}
+
+/// A single meaning of a word
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Meaning {
+    pub glosses: Vec<String>,
+    pub part_of_speech: Vec<String>,
+}
+
+/// Grows `meanings` to at least `len` entries, creating it if needed
+fn meanings_mut(meanings: &mut Option<Vec<Meaning>>, len: usize) -> &mut Vec<Meaning> {
+    let meanings = meanings.get_or_insert_with(Vec::new);
+    if meanings.len() < len {
+        meanings.resize_with(len, Meaning::default);
+    }
+    meanings
+}
+
+/// The result of [`Client::parse_text`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseResult {
+    pub tokens: Vec<Token>,
+    pub vocabulary: Vec<VocabularyEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ParseResponse {
+    tokens: Vec<Vec<serde_json::Value>>,
+    vocabulary: Vec<Vec<serde_json::Value>>,
+}
+
+/// A struct that jpdb sends as a positional row, whose values are in the same order as the requested fields.
+pub(crate) trait FromRow: Default {
+    type Field: Clone + Serialize;
+
+    /// Decodes a single value of the row into the member matching `field`
+    fn set_field(
+        &mut self,
+        field: Self::Field,
+        value: serde_json::Value,
+    ) -> Result<(), serde_json::Error>;
+}
+
+/// Removes duplicated fields while keeping their order, since the rows will follow it.
+pub(crate) fn dedup_fields<F: Clone + PartialEq>(fields: &[F]) -> Vec<F> {
+    let mut deduped = Vec::with_capacity(fields.len());
+    for field in fields {
+        if !deduped.contains(field) {
+            deduped.push(field.clone());
+        }
+    }
+    deduped
+}
+
+fn field_name<F: Serialize>(field: &F) -> String {
+    match serde_json::to_value(field) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::from("?"),
+    }
+}
+
+/// Decodes the row at `index` of a response, according to the deduplicated `fields` sent in the request.
+pub(crate) fn decode_row<T: FromRow>(
+    index: usize,
+    fields: &[T::Field],
+    row: Vec<serde_json::Value>,
+) -> Result<T, Error> {
+    if row.len() != fields.len() {
+        return Err(Error::UnexpectedResponse(format!(
+            "row {index} has {} values, expected {}",
+            row.len(),
+            fields.len()
+        )));
+    }
+    let mut decoded = T::default();
+    for (field, value) in fields.iter().zip(row) {
+        decoded.set_field(field.clone(), value).map_err(|e| {
+            Error::UnexpectedResponse(format!("row {index}, field \"{}\": {e}", field_name(field)))
+        })?;
+    }
+    Ok(decoded)
+}
+
+/// Decodes every row of a response, see [`decode_row`].
+pub(crate) fn decode_rows<T: FromRow>(
+    fields: &[T::Field],
+    rows: Vec<Vec<serde_json::Value>>,
+) -> Result<Vec<T>, Error> {
+    rows.into_iter()
+        .enumerate()
+        .map(|(index, row)| decode_row(index, fields, row))
+        .collect()
+}
+
+impl FromRow for Token {
+    type Field = TokenQueryField;
+
+    fn set_field(
+        &mut self,
+        field: TokenQueryField,
+        value: serde_json::Value,
+    ) -> Result<(), serde_json::Error> {
+        match field {
+            TokenQueryField::VocabIndex => self.vocabulary_index = serde_json::from_value(value)?,
+            TokenQueryField::PositionUtf8 => self.position_utf8 = serde_json::from_value(value)?,
+            TokenQueryField::PositionUtf32 => self.position_utf32 = serde_json::from_value(value)?,
+            TokenQueryField::LengthUtf8 => self.length_utf8 = serde_json::from_value(value)?,
+            TokenQueryField::LengthUtf32 => self.length_utf32 = serde_json::from_value(value)?,
+            TokenQueryField::Furigana => self.furigana = serde_json::from_value(value)?,
+        }
+        Ok(())
+    }
+}
+
+impl FromRow for VocabularyEntry {
+    type Field = VocabQueryField;
+
+    fn set_field(
+        &mut self,
+        field: VocabQueryField,
+        value: serde_json::Value,
+    ) -> Result<(), serde_json::Error> {
+        match field {
+            VocabQueryField::Vid => self.vid = serde_json::from_value(value)?,
+            VocabQueryField::Sid => self.sid = serde_json::from_value(value)?,
+            VocabQueryField::Rid => self.rid = serde_json::from_value(value)?,
+            VocabQueryField::Spelling => self.spelling = serde_json::from_value(value)?,
+            VocabQueryField::Reading => self.reading = serde_json::from_value(value)?,
+            VocabQueryField::FrequencyRank => self.frequency_rank = serde_json::from_value(value)?,
+            VocabQueryField::Meanings => self.meanings = serde_json::from_value(value)?,
+            VocabQueryField::CardLevel => self.card_level = serde_json::from_value(value)?,
+            VocabQueryField::CardState => self.card_state = serde_json::from_value(value)?,
+            VocabQueryField::DueAt => self.due_at = serde_json::from_value(value)?,
+            VocabQueryField::PartOfSpeech => self.part_of_speech = serde_json::from_value(value)?,
+            VocabQueryField::MeaningsChunks => {
+                let chunks: Option<Vec<Vec<String>>> = serde_json::from_value(value)?;
+                if let Some(chunks) = chunks {
+                    let meanings = meanings_mut(&mut self.meaning_chunks, chunks.len());
+                    for (meaning, glosses) in meanings.iter_mut().zip(chunks) {
+                        meaning.glosses = glosses;
+                    }
+                }
+            }
+            VocabQueryField::MeaningsPartOfSpeech => {
+                let parts: Option<Vec<Vec<String>>> = serde_json::from_value(value)?;
+                if let Some(parts) = parts {
+                    let meanings = meanings_mut(&mut self.meaning_chunks, parts.len());
+                    for (meaning, part_of_speech) in meanings.iter_mut().zip(parts) {
+                        meaning.part_of_speech = part_of_speech;
+                    }
+                }
+            }
+            VocabQueryField::AltSids => self.alt_sids = serde_json::from_value(value)?,
+            VocabQueryField::AltSpellings => self.alt_spellings = serde_json::from_value(value)?,
+            VocabQueryField::PitchAccent => self.pitch_accent = serde_json::from_value(value)?,
+            VocabQueryField::Other(name) => match VocabQueryField::from_name(&name) {
+                VocabQueryField::Other(name) => {
+                    self.other.insert(name, value);
+                }
+                known => return self.set_field(known, value),
+            },
+        }
+        Ok(())
+    }
+}
+
+impl ParseResult {
+    pub fn from_response(
+        token_fields: &[TokenQueryField],
+        vocab_fields: &[VocabQueryField],
+        response: ParseResponse,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            tokens: decode_rows(token_fields, response.tokens)?,
+            vocabulary: decode_rows(vocab_fields, response.vocabulary)?,
+        })
+    }
+}
+
+/// The result of [`Client::parse_texts`], with one list of tokens per text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseTextsResult {
+    pub tokens: Vec<Vec<Token>>,
+    pub vocabulary: Vec<VocabularyEntry>,
+}
+
+/// What jpdb answers with when parsing several texts at once
+#[derive(Deserialize, Debug)]
+pub struct ParseTextsResponse {
+    tokens: Vec<Vec<Vec<serde_json::Value>>>,
+    vocabulary: Vec<Vec<serde_json::Value>>,
+}
+
+impl ParseTextsResult {
+    pub fn from_response(
+        texts: usize,
+        token_fields: &[TokenQueryField],
+        vocab_fields: &[VocabQueryField],
+        response: ParseTextsResponse,
+    ) -> Result<Self, Error> {
+        if response.tokens.len() != texts {
+            return Err(Error::UnexpectedResponse(format!(
+                "parsed {texts} texts, got {} token lists",
+                response.tokens.len()
+            )));
+        }
+        Ok(Self {
+            tokens: response
+                .tokens
+                .into_iter()
+                .map(|rows| decode_rows(token_fields, rows))
+                .collect::<Result<_, _>>()?,
+            vocabulary: decode_rows(vocab_fields, response.vocabulary)?,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LookupVocabularyResponse {
+    vocabulary_info: Vec<Option<Vec<serde_json::Value>>>,
+}
+
+impl LookupVocabularyResponse {
+    /// Pairs every looked up word with its entry, which is `None` if jpdb doesn't know about it
+    pub fn into_entries(
+        self,
+        list: &[(Vid, Sid)],
+        fields: &[VocabQueryField],
+    ) -> Result<HashMap<(Vid, Sid), Option<VocabularyEntry>>, Error> {
+        if self.vocabulary_info.len() != list.len() {
+            return Err(Error::UnexpectedResponse(format!(
+                "looked up {} words, got {} entries",
+                list.len(),
+                self.vocabulary_info.len()
+            )));
+        }
+        list.iter()
+            .zip(self.vocabulary_info)
+            .enumerate()
+            .map(|(index, (&key, row))| {
+                let entry = row.map(|row| decode_row(index, fields, row)).transpose()?;
+                Ok((key, entry))
+            })
+            .collect()
+    }
+}
+
+/// A deck returned by the API. Only the fields that were requested are populated.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Deck {
+    pub id: Option<AnyDeckWidget>,
+    pub name: Option<String>,
+    pub vocabulary_count: Option<u32>,
+    pub word_count: Option<u32>,
+    pub known_coverage: Option<f64>,
+    pub in_progress_coverage: Option<f64>,
+    pub is_built_in: Option<bool>,
+}
+
+impl FromRow for Deck {
+    type Field = DeckQueryField;
+
+    fn set_field(
+        &mut self,
+        field: DeckQueryField,
+        value: serde_json::Value,
+    ) -> Result<(), serde_json::Error> {
+        match field {
+            DeckQueryField::Id => self.id = serde_json::from_value(value)?,
+            DeckQueryField::Name => self.name = serde_json::from_value(value)?,
+            DeckQueryField::VocabularyCount => {
+                self.vocabulary_count = serde_json::from_value(value)?
+            }
+            DeckQueryField::WordCount => self.word_count = serde_json::from_value(value)?,
+            DeckQueryField::KnownCoverage => self.known_coverage = serde_json::from_value(value)?,
+            DeckQueryField::InProgressCoverage => {
+                self.in_progress_coverage = serde_json::from_value(value)?
+            }
+            DeckQueryField::IsBuiltIn => self.is_built_in = serde_json::from_value(value)?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ListDecksResponse {
+    decks: Vec<Vec<serde_json::Value>>,
+}
+
+impl ListDecksResponse {
+    pub fn into_decks(self, fields: &[DeckQueryField]) -> Result<Vec<Deck>, Error> {
+        decode_rows(fields, self.decks)
+    }
+}
+
 pub enum DeckTypeToList {
     UserDecks,
     SpecialDecks,
@@ -246,13 +665,96 @@ impl DeckTypeToList {
     }
 }
 
+impl Request {
+    /// Prepares `endpoint` to be sent to the server at `base_url`. Fails with
+    /// [`Error::SerializeError`] if `endpoint` can't be serialized to JSON.
+    pub fn new<E: Endpoint>(base_url: &str, endpoint: &E) -> Result<Self, Error> {
+        Ok(Request {
+            url: Client::create_url(base_url, E::PATH),
+            body: serde_json::to_value(endpoint).map_err(Error::SerializeError)?,
+        })
+    }
+}
+
+/// Makes sure [`DeckQueryField::Id`] is part of the fields, so that decks can be tagged with it
+pub(crate) fn with_id_field(fields: &[DeckQueryField]) -> Vec<DeckQueryField> {
+    let mut fields = fields.to_vec();
+    if !fields.contains(&DeckQueryField::Id) {
+        fields.push(DeckQueryField::Id);
+    }
+    fields
+}
+
+pub(crate) fn tag_decks(decks: Vec<Deck>) -> Result<Vec<(AnyDeckWidget, Deck)>, Error> {
+    decks
+        .into_iter()
+        .map(|deck| match deck.id.clone() {
+            Some(id) => Ok((id, deck)),
+            None => Err(Error::UnexpectedResponse(String::from(
+                "deck is missing its id",
+            ))),
+        })
+        .collect()
+}
+
+impl DeckVocabulary {
+    pub(crate) fn into_occurence_map(self) -> Result<HashMap<Vocabulary, u32>, Error> {
+        let Some(occurences) = self.occurences else {
+            return Err(Error::UnexpectedResponse(String::from(
+                "asked for occurences but the server didn't return them",
+            )));
+        };
+        let mut map = HashMap::<Vocabulary, u32>::new();
+        map.extend(self.vocabulary.iter().zip(occurences.iter()));
+        Ok(map)
+    }
+
+    /// Like [`DeckVocabulary::into_occurence_map`], but keeps the order of the deck
+    pub(crate) fn into_occurence_list(self) -> Result<Vec<(Vocabulary, u32)>, Error> {
+        match self.occurences {
+            Some(occurences) if occurences.len() == self.vocabulary.len() => {
+                Ok(self.vocabulary.into_iter().zip(occurences).collect())
+            }
+            _ => Err(Error::UnexpectedResponse(String::from(
+                "asked for occurences but the server didn't return one per word",
+            ))),
+        }
+    }
+}
+
 impl Client {
-    pub fn ping(&self) -> Result<(), Error> {
+    /// Sends any [`Endpoint`], including ones defined outside of this crate.
+    ///
+    /// Fails with [`Error::InvalidRequest`] without sending anything if [`Endpoint::validate`] does.
+    pub fn execute<E: Endpoint>(&self, endpoint: &E) -> Result<E::Response, Error> {
+        endpoint.validate()?;
+        let request = Request::new(&self.base_url, endpoint)?;
+        self.send_request(request, endpoint.is_idempotent())?
+            .into_json::<E::Response>()
+            .map_err(Error::DeserializeError)
+    }
+
+    /// Posts `body` to `path`, relative to the base URL, and returns whatever JSON jpdb answers
+    /// with. Meant for endpoints this crate doesn't wrap yet, see [`Client::execute`] for typed ones.
+    ///
+    /// As nothing is known about the endpoint, it's treated as not
+    /// [idempotent](Endpoint::is_idempotent) when retrying.
+    pub fn call_raw(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, Error> {
         let request = Request {
-            url: Client::create_url(self.base_url, "ping"),
-            body: serde_json::Value::Null,
+            url: Client::create_url(&self.base_url, path.trim_start_matches('/')),
+            body: body.clone(),
         };
-        self.send_request(request)?;
+        self.send_request(request, false)?
+            .into_json::<serde_json::Value>()
+            .map_err(Error::DeserializeError)
+    }
+
+    pub fn ping(&self) -> Result<(), Error> {
+        self.execute(&endpoint::Ping)?;
         Ok(())
     }
 
@@ -261,113 +763,69 @@ impl Client {
         text: &str,
         token_fields: &[TokenQueryField],
         vocab_fields: Option<&[VocabQueryField]>,
-    ) -> Result<(), Error> {
-        let mut token_fields = token_fields.to_vec();
-        // fields.sort(); // TODO?
-        token_fields.dedup();
-
-        let body = if let Some(fields) = vocab_fields {
-            let mut vocab_fields = fields.to_vec();
-            // fields.sort(); // TODO?
-            vocab_fields.dedup();
-            json!({
-                "text": text,
-                "token_fields": token_fields,
-                "vocabulary_fields": vocab_fields,
-            })
-        } else {
-            json!({
-                "text": text,
-                "token_fields": token_fields,
-            })
-        };
-
-        let request = Request {
-            url: Client::create_url(self.base_url, "parse"),
-            body,
-        };
-        let response = self
-            .send_request(request)?
-            .into_json::<serde_json::Value>()
-            .map_err(Error::DeserializeError)?;
-        dbg!(response);
-        //TODO deserialize
-        unimplemented!()
+    ) -> Result<ParseResult, Error> {
+        let token_fields = dedup_fields(token_fields);
+        // The vocabulary rows can't be decoded without knowing their layout, so always send it
+        let vocab_fields = dedup_fields(vocab_fields.unwrap_or_default());
+        let response = self.execute(&endpoint::ParseText {
+            text,
+            token_fields: &token_fields,
+            vocabulary_fields: &vocab_fields,
+        })?;
+        ParseResult::from_response(&token_fields, &vocab_fields, response)
     }
 
     pub fn lookup_vocabulary(
         &self,
         list: &[(Vid, Sid)],
         fields: &[VocabQueryField],
-    ) -> Result<(), Error> {
-        let mut fields = fields.to_vec();
-        // fields.sort(); // TODO?
-        fields.dedup();
-        let request = Request {
-            url: Client::create_url(self.base_url, "lookup-vocabulary"),
-            body: json! ({
-                "list": list,
-                "fields": fields,
-            }),
-        };
-        let _response = self
-            .send_request(request)?
-            .into_json::<serde_json::Value>()
-            .map_err(Error::DeserializeError)?;
-        //TODO deserialize
-        unimplemented!()
+    ) -> Result<HashMap<(Vid, Sid), Option<VocabularyEntry>>, Error> {
+        let fields = dedup_fields(fields);
+        let response = self.execute(&endpoint::LookupVocabulary {
+            list,
+            fields: &fields,
+        })?;
+        response.into_entries(list, &fields)
     }
 
     pub fn list_decks_raw(
         &self,
         fields: &[DeckQueryField],
         deck_type: DeckTypeToList,
-    ) -> Result<(), Error> {
-        let mut fields = fields.to_vec();
-        // fields.sort(); // TODO?
-        fields.dedup();
-        let _request = Request {
-            url: Client::create_url(self.base_url, deck_type.as_str()),
-            body: json! ({
-                "fields": fields,
-            }),
-        };
-        // TODO destructure the reply
-        unimplemented!();
-        // Ok(())
+    ) -> Result<Vec<Deck>, Error> {
+        let fields = dedup_fields(fields);
+        let response = match deck_type {
+            DeckTypeToList::UserDecks => self.execute(&endpoint::ListUserDecks { fields: &fields }),
+            DeckTypeToList::SpecialDecks => {
+                self.execute(&endpoint::ListSpecialDecks { fields: &fields })
+            }
+        }?;
+        response.into_decks(&fields)
     }
 
-    pub fn list_special_decks(&self, fields: &[DeckQueryField]) -> Result<(), Error> {
-        let _decks = self.list_decks_raw(fields, DeckTypeToList::SpecialDecks)?;
-        unimplemented!();
-        // Ok(())
+    pub fn list_special_decks(&self, fields: &[DeckQueryField]) -> Result<Vec<Deck>, Error> {
+        self.list_decks_raw(fields, DeckTypeToList::SpecialDecks)
     }
 
-    pub fn list_user_decks(&self, fields: &[DeckQueryField]) -> Result<(), Error> {
-        let _decks = self.list_decks_raw(fields, DeckTypeToList::UserDecks)?;
-        unimplemented!();
-        // Ok(())
+    pub fn list_user_decks(&self, fields: &[DeckQueryField]) -> Result<Vec<Deck>, Error> {
+        self.list_decks_raw(fields, DeckTypeToList::UserDecks)
     }
 
-    pub fn list_all_decks(&self, _fields: &[DeckQueryField]) -> Result<(), Error> {
-        unimplemented!();
-        // Ok(())
+    /// Lists the user decks, followed by the special decks, each tagged with its id.
+    ///
+    /// [`DeckQueryField::Id`] is always requested, so [`Deck::id`] will be populated too.
+    pub fn list_all_decks(
+        &self,
+        fields: &[DeckQueryField],
+    ) -> Result<Vec<(AnyDeckWidget, Deck)>, Error> {
+        let fields = with_id_field(fields);
+        let mut decks = self.list_user_decks(&fields)?;
+        decks.extend(self.list_special_decks(&fields)?);
+        tag_decks(decks)
     }
 
     pub fn create_empty_deck(&self, name: &str, position: Option<u8>) -> Result<UserDeckId, Error> {
-        let body = if let Some(p) = position {
-            json!({"name": name, "position": p})
-        } else {
-            json!({ "name": name })
-        };
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/create-empty"),
-            body,
-        };
-        let response = self
-            .send_request(request)?
-            .into_json::<CreateEmptyDeckResponse>()
-            .map_err(Error::DeserializeError)?;
+        let response = self.execute(&endpoint::CreateEmptyDeck { name, position })?;
         Ok(response.into())
     }
 
@@ -376,25 +834,10 @@ impl Client {
         deck_id: impl AnyDeckId,
         fetch_occurence: Option<bool>,
     ) -> Result<DeckVocabulary, Error> {
-        let body = if let Some(fetch) = fetch_occurence {
-            json!({
-                "id": deck_id.as_any(),
-                "fetch_occurences": fetch,
-            })
-        } else {
-            json!({
-                "id": deck_id.as_any()
-            })
-        };
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/list-vocabulary"),
-            body,
-        };
-        let response = self
-            .send_request(request)?
-            .into_json::<DeckVocabulary>()
-            .map_err(Error::DeserializeError)?;
-        Ok(response)
+        self.execute(&endpoint::ListVocabulary {
+            id: deck_id.as_any(),
+            fetch_occurences: fetch_occurence,
+        })
     }
 
     pub fn list_vocabulary(&self, deck_id: impl AnyDeckId) -> Result<Vec<Vocabulary>, Error> {
@@ -406,90 +849,54 @@ impl Client {
         &self,
         deck_id: impl AnyDeckId,
     ) -> Result<HashMap<Vocabulary, u32>, Error> {
-        let raw = self.list_vocabulary_raw(deck_id, Some(true))?;
-        let Some(occurences) = raw.occurences else {
-            // return Err(Error::DeserializeError(String::from("asked for occurences but the server didn't return them. This error shouldn't be happening.")));
-            panic!("todo change me fix above pleaseeee");
-        };
-        let mut map = HashMap::<Vocabulary, u32>::new();
-        map.extend(raw.vocabulary.iter().zip(occurences.iter()));
-        Ok(map)
+        self.list_vocabulary_raw(deck_id, Some(true))?
+            .into_occurence_map()
     }
 
+    /// Adds the vocabulary in chunks of [`ClientBuilder::chunk_size`](crate::client::ClientBuilder::chunk_size)
+    /// words, see [`Client::add_vocabulary_with_progress`]
     pub fn add_vocabulary(
         &self,
         deck_id: impl AnyDeckId,
         options: &AddVocabularyOptions,
     ) -> Result<(), Error> {
-        let mut body = json!(options).as_object_mut().unwrap().clone();
-        body.insert("id".to_string(), json!(deck_id.as_any()));
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/add-vocabulary"),
-            body: json!(body),
-        };
-        self.send_request(request)?;
-        Ok(())
+        self.add_vocabulary_with_progress(deck_id, options, |_| {})
     }
 
+    /// Removes the vocabulary in chunks, see [`Client::remove_vocabulary_with_progress`]
     pub fn remove_vocabulary(
         &self,
         deck_id: impl AnyDeckId,
         vocabulary: &[Vocabulary],
     ) -> Result<(), Error> {
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/remove-vocabulary"),
-            body: json!({
-                "id": deck_id.as_any(),
-                "vocabulary": vocabulary,
-            }),
-        };
-        self.send_request(request)?;
-        Ok(())
+        self.remove_vocabulary_with_progress(deck_id, vocabulary, |_| {})
     }
 
     pub fn clear_deck(&self, deck_id: impl AnyDeckId) -> Result<(), Error> {
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/clear"),
-            body: json!({
-                "id": deck_id.as_any(),
-            }),
-        };
-        self.send_request(request)?;
+        self.execute(&endpoint::ClearDeck {
+            id: deck_id.as_any(),
+        })?;
         Ok(())
     }
 
     pub fn delete_deck(&self, deck_id: UserDeckId) -> Result<(), Error> {
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/delete"),
-            body: json!({
-                "id": deck_id.as_any(),
-            }),
-        };
-        self.send_request(request)?;
+        self.execute(&endpoint::DeleteDeck {
+            id: deck_id.as_any(),
+        })?;
         Ok(())
     }
 
     pub fn rename_deck(&self, deck_id: UserDeckId, new_name: &str) -> Result<(), Error> {
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/rename"),
-            body: json!({
-                "id": deck_id.as_any(),
-                "name": new_name,
-            }),
-        };
-        dbg!(&request);
-        self.send_request(request)?;
+        self.execute(&endpoint::RenameDeck {
+            id: deck_id.as_any(),
+            name: new_name,
+        })?;
         Ok(())
     }
 
     //TODO change, take vocab, options is optional
     pub fn set_card_sentence(&self, options: &SetCardSentenceOptions) -> Result<(), Error> {
-        //TODO change the url in next jpdb patch
-        let request = Request {
-            url: Client::create_url(self.base_url, "deck/set-card-sentence"),
-            body: json!(options),
-        };
-        self.send_request(request)?;
+        self.execute(options)?;
         Ok(())
     }
 }
@@ -0,0 +1,183 @@
+//! Reconciles a deck with a desired list of words, see
+//! [`Client::sync_deck`](crate::client::Client::sync_deck).
+
+use std::collections::HashMap;
+
+#[cfg(feature = "async")]
+use crate::async_client::AsyncClient;
+use crate::{
+    client::Client,
+    error::Error,
+    request::{AddVocabularyOptions, AnyDeckId, Vocabulary},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncOptions {
+    /// Computes the report without changing the deck
+    pub dry_run: bool,
+    /// Leaves the occurences of the words already in the deck as they are, and adds new ones
+    /// without any
+    pub ignore_occurences: bool,
+}
+
+/// A word whose occurences were, or would be in a dry run, overwritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OccurenceChange {
+    pub vocabulary: Vocabulary,
+    pub from: u32,
+    pub to: u16,
+}
+
+/// What [`Client::sync_deck`](crate::client::Client::sync_deck) changed, or would change in a dry
+/// run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// In the order of the desired list
+    pub added: Vec<Vocabulary>,
+    /// Sorted by vid and sid
+    pub removed: Vec<Vocabulary>,
+    /// In the order of the desired list
+    pub updated: Vec<OccurenceChange>,
+    /// How many words were already in the deck as desired
+    pub unchanged: usize,
+    pub dry_run: bool,
+}
+
+impl SyncReport {
+    /// Whether the deck already matched the desired list
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// The changes needed to go from `current` to `desired`, and the words and occurences to send to
+/// `add_vocabulary`. New words and updated occurences go in the same request, since overwriting
+/// the occurences of a word that isn't in the deck yet just adds it.
+pub(crate) struct SyncPlan {
+    pub(crate) report: SyncReport,
+    pub(crate) to_add: Vec<Vocabulary>,
+    pub(crate) occurences: Vec<u16>,
+}
+
+/// Duplicated words in `desired` are merged, and their occurences summed
+pub(crate) fn plan(
+    current: &HashMap<Vocabulary, u32>,
+    desired: &[(Vocabulary, u16)],
+    options: SyncOptions,
+) -> SyncPlan {
+    let mut merged: Vec<(Vocabulary, u16)> = Vec::with_capacity(desired.len());
+    let mut indices = HashMap::<Vocabulary, usize>::new();
+    for &(vocabulary, occurences) in desired {
+        match indices.get(&vocabulary) {
+            Some(&index) => {
+                let count = &mut merged[index].1;
+                *count = count.saturating_add(occurences);
+            }
+            None => {
+                indices.insert(vocabulary, merged.len());
+                merged.push((vocabulary, occurences));
+            }
+        }
+    }
+
+    let mut report = SyncReport {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+    let mut to_add = Vec::new();
+    let mut occurences = Vec::new();
+    for (vocabulary, count) in merged {
+        match current.get(&vocabulary) {
+            None => report.added.push(vocabulary),
+            Some(&existing) if !options.ignore_occurences && existing != u32::from(count) => {
+                report.updated.push(OccurenceChange {
+                    vocabulary,
+                    from: existing,
+                    to: count,
+                });
+            }
+            Some(_) => {
+                report.unchanged += 1;
+                continue;
+            }
+        }
+        to_add.push(vocabulary);
+        occurences.push(count);
+    }
+    report.removed = current
+        .keys()
+        .filter(|vocabulary| !indices.contains_key(vocabulary))
+        .copied()
+        .collect();
+    report.removed.sort_unstable();
+    SyncPlan {
+        report,
+        to_add,
+        occurences,
+    }
+}
+
+impl SyncPlan {
+    pub(crate) fn add_options(&self, options: SyncOptions) -> AddVocabularyOptions<'_> {
+        if options.ignore_occurences {
+            AddVocabularyOptions {
+                vocabulary: &self.to_add,
+                ..Default::default()
+            }
+        } else {
+            AddVocabularyOptions {
+                vocabulary: &self.to_add,
+                occurences: Some(&self.occurences),
+                overwrite_occurences: Some(true),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Implements [`Client::sync_deck`] for a client, with `async await` for [`AsyncClient`], so that
+/// both clients apply the plan the same way
+macro_rules! impl_sync {
+    ($client:ty $(, $async:tt $await:tt)?) => {
+        impl $client {
+            /// Makes the deck contain exactly the `desired` words with the given occurences, by
+            /// removing the words that aren't desired, then adding the missing ones and
+            /// overwriting the occurences that differ in a single batch.
+            ///
+            /// Words already in the deck keep their position. If a request fails midway, syncing
+            /// again picks up where it stopped.
+            pub $($async)? fn sync_deck(
+                &self,
+                deck_id: impl AnyDeckId,
+                desired: &[(Vocabulary, u16)],
+                options: SyncOptions,
+            ) -> Result<SyncReport, Error> {
+                let id = deck_id.as_any();
+                let current = if options.ignore_occurences {
+                    self.list_vocabulary(&id)
+                        $(.$await)? ?
+                        .into_iter()
+                        .map(|vocabulary| (vocabulary, 0))
+                        .collect()
+                } else {
+                    self.list_vocabulary_with_occurences(&id) $(.$await)? ?
+                };
+                let plan = plan(&current, desired, options);
+                if options.dry_run {
+                    return Ok(plan.report);
+                }
+                if !plan.report.removed.is_empty() {
+                    self.remove_vocabulary(&id, &plan.report.removed) $(.$await)? ?;
+                }
+                if !plan.to_add.is_empty() {
+                    self.add_vocabulary(id, &plan.add_options(options)) $(.$await)? ?;
+                }
+                Ok(plan.report)
+            }
+        }
+    };
+}
+
+impl_sync!(Client);
+#[cfg(feature = "async")]
+impl_sync!(AsyncClient, async await);
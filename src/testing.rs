@@ -0,0 +1,750 @@
+//! An in-memory stand-in for jpdb, available with the `testing` feature.
+//!
+//! [`FakeJpdb`] implements [`Transport`], so a [`Client`], or an `AsyncClient` with the `async`
+//! feature, can be pointed at it to exercise code that talks to jpdb without any network access.
+//! It keeps track of decks, their vocabulary and occurences, and card sentences, and answers with
+//! the same errors jpdb documents.
+//!
+//! ```
+//! use jpdb::{request::{AddVocabularyOptions, Vocabulary}, testing::{FakeJpdb, FakeWord}};
+//!
+//! let fake = FakeJpdb::new("token");
+//! fake.add_word(FakeWord::new(Vocabulary(1, 2), "猫", "ねこ"));
+//! let client = fake.client();
+//!
+//! let deck = client.create_empty_deck("Animals", None)?;
+//! client.add_vocabulary(deck, &AddVocabularyOptions {
+//!     vocabulary: &[Vocabulary(1, 2)],
+//!     ..Default::default()
+//! })?;
+//! assert_eq!(client.list_vocabulary(deck)?, vec![Vocabulary(1, 2)]);
+//! # Ok::<(), jpdb::error::Error>(())
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+#[cfg(feature = "async")]
+use crate::async_client::AsyncClient;
+use crate::{
+    client::{Client, DEFAULT_BASE_URL},
+    request::{AnyDeckId, AnyDeckWidget, Request, UserDeckId, Vocabulary},
+    transport::{HttpResponse, Transport, TransportError},
+};
+
+/// A word known to the fake dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeWord {
+    pub vocabulary: Vocabulary,
+    pub rid: u32,
+    pub spelling: String,
+    pub reading: String,
+    pub frequency_rank: Option<u32>,
+    pub meanings: Vec<String>,
+    /// Shared by every meaning
+    pub part_of_speech: Vec<String>,
+    pub pitch_accent: Vec<String>,
+}
+
+impl FakeWord {
+    pub fn new(vocabulary: Vocabulary, spelling: &str, reading: &str) -> Self {
+        Self {
+            vocabulary,
+            rid: 0,
+            spelling: spelling.to_owned(),
+            reading: reading.to_owned(),
+            frequency_rank: None,
+            meanings: Vec::new(),
+            part_of_speech: Vec::new(),
+            pitch_accent: Vec::new(),
+        }
+    }
+}
+
+/// What was set with [`Client::set_card_sentence`] for a card.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FakeCardSentence {
+    pub sentence: Option<String>,
+    pub translation: Option<String>,
+    pub has_image: bool,
+    pub has_audio: bool,
+}
+
+#[derive(Debug, Clone)]
+struct FakeDeck {
+    id: AnyDeckWidget,
+    name: String,
+    vocabulary: Vec<(Vocabulary, u32)>,
+}
+
+impl FakeDeck {
+    fn new(id: AnyDeckWidget, name: &str) -> Self {
+        Self {
+            id,
+            name: name.to_owned(),
+            vocabulary: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    token: String,
+    base_url: String,
+    words: Vec<FakeWord>,
+    user_decks: Vec<FakeDeck>,
+    special_decks: Vec<FakeDeck>,
+    next_deck_id: u8,
+    card_sentences: HashMap<Vocabulary, FakeCardSentence>,
+    failures: VecDeque<(u16, String, String)>,
+    requests: Vec<Request>,
+}
+
+/// The error part of a response: status, `error` and `error_message`
+type Failure = (u16, &'static str, String);
+
+fn bad_request(message: impl Into<String>) -> Failure {
+    (400, "bad_request", message.into())
+}
+
+fn parse_body<T: DeserializeOwned>(body: &Value) -> Result<T, Failure> {
+    serde_json::from_value(body.clone()).map_err(|e| bad_request(e.to_string()))
+}
+
+fn check_fields<'a>(fields: &'a [String], known: &[&str]) -> Result<&'a [String], Failure> {
+    match fields.iter().find(|f| !known.contains(&f.as_str())) {
+        Some(unknown) => Err(bad_request(format!("unknown field: {unknown}"))),
+        None => Ok(fields),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldsBody {
+    fields: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeckIdBody {
+    id: AnyDeckWidget,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CreateEmptyDeckBody {
+    name: String,
+    position: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RenameDeckBody {
+    id: AnyDeckWidget,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ListVocabularyBody {
+    id: AnyDeckWidget,
+    fetch_occurences: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddVocabularyBody {
+    id: AnyDeckWidget,
+    vocabulary: Vec<Vocabulary>,
+    occurences: Option<Vec<u32>>,
+    replace_existing_occurences: Option<bool>,
+    ignore_unknown: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RemoveVocabularyBody {
+    id: AnyDeckWidget,
+    vocabulary: Vec<Vocabulary>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SetCardSentenceBody {
+    vid: u32,
+    sid: u32,
+    sentence: Option<String>,
+    translation: Option<String>,
+    clear_audio: Option<bool>,
+    clear_image: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LookupVocabularyBody {
+    list: Vec<Vocabulary>,
+    fields: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParseBody {
+    text: ParseText,
+    token_fields: Vec<String>,
+    vocabulary_fields: Vec<String>,
+}
+
+/// jpdb parses either a single text, or several ones sharing a vocabulary table
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ParseText {
+    Single(String),
+    Many(Vec<String>),
+}
+
+const DECK_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "vocabulary_count",
+    "word_count",
+    "vocabulary_known_coverage",
+    "vocabulary_in_progress_coverage",
+    "is_built_in",
+];
+
+const VOCABULARY_FIELDS: &[&str] = &[
+    "vid",
+    "sid",
+    "rid",
+    "spelling",
+    "reading",
+    "frequency_rank",
+    "meanings",
+    "card_level",
+    "card_state",
+    "due_at",
+    "part_of_speech",
+    "meanings_chunks",
+    "meanings_part_of_speech",
+    "alt_sids",
+    "alt_spellings",
+    "pitch_accent",
+];
+
+const TOKEN_FIELDS: &[&str] = &[
+    "vocabulary_index",
+    "position_utf8",
+    "position_utf32",
+    "length_utf8",
+    "length_utf32",
+    "furigana",
+];
+
+impl State {
+    fn deck(&self, id: &AnyDeckWidget) -> Result<&FakeDeck, Failure> {
+        self.user_decks
+            .iter()
+            .chain(&self.special_decks)
+            .find(|deck| deck.id == *id)
+            .ok_or_else(|| (400, "bad_deck", format!("deck not found: {id:?}")))
+    }
+
+    fn deck_mut(&mut self, id: &AnyDeckWidget) -> Result<&mut FakeDeck, Failure> {
+        self.user_decks
+            .iter_mut()
+            .chain(&mut self.special_decks)
+            .find(|deck| deck.id == *id)
+            .ok_or_else(|| (400, "bad_deck", format!("deck not found: {id:?}")))
+    }
+
+    fn word(&self, vocabulary: Vocabulary) -> Result<&FakeWord, Failure> {
+        if !self.words.iter().any(|w| w.vocabulary.0 == vocabulary.0) {
+            return Err((400, "bad_vid", format!("vid not found: {}", vocabulary.0)));
+        }
+        self.words
+            .iter()
+            .find(|w| w.vocabulary == vocabulary)
+            .ok_or_else(|| (400, "bad_sid", format!("sid not found: {}", vocabulary.1)))
+    }
+
+    fn card_state(&self, vocabulary: Vocabulary) -> Value {
+        let in_deck = |id| {
+            self.deck(&id)
+                .map(|deck| deck.vocabulary.iter().any(|(v, _)| *v == vocabulary))
+                .unwrap_or(false)
+        };
+        if in_deck(AnyDeckWidget::Blacklist) {
+            json!(["blacklisted"])
+        } else if in_deck(AnyDeckWidget::NeverForget) {
+            json!(["never-forget"])
+        } else if self
+            .user_decks
+            .iter()
+            .any(|deck| deck.vocabulary.iter().any(|(v, _)| *v == vocabulary))
+        {
+            json!(["new"])
+        } else {
+            Value::Null
+        }
+    }
+
+    fn vocabulary_row(&self, word: &FakeWord, fields: &[String]) -> Value {
+        let in_deck = !self.card_state(word.vocabulary).is_null();
+        fields
+            .iter()
+            .map(|field| match field.as_str() {
+                "vid" => json!(word.vocabulary.0),
+                "sid" => json!(word.vocabulary.1),
+                "rid" => json!(word.rid),
+                "spelling" => json!(word.spelling),
+                "reading" => json!(word.reading),
+                "frequency_rank" => json!(word.frequency_rank),
+                "meanings" => json!(word.meanings),
+                "card_level" if in_deck => json!(0),
+                "card_state" => self.card_state(word.vocabulary),
+                "part_of_speech" => json!(word.part_of_speech),
+                "meanings_chunks" => {
+                    let chunks: Vec<_> = word.meanings.iter().map(|m| [m]).collect();
+                    json!(chunks)
+                }
+                "meanings_part_of_speech" => {
+                    json!(vec![&word.part_of_speech; word.meanings.len()])
+                }
+                "alt_sids" | "alt_spellings" => json!([]),
+                "pitch_accent" if !word.pitch_accent.is_empty() => json!(word.pitch_accent),
+                _ => Value::Null,
+            })
+            .collect()
+    }
+
+    fn deck_row(deck: &FakeDeck, fields: &[String]) -> Value {
+        fields
+            .iter()
+            .map(|field| match field.as_str() {
+                "id" => json!(deck.id),
+                "name" => json!(deck.name),
+                "vocabulary_count" => json!(deck.vocabulary.len()),
+                "word_count" => json!(deck.vocabulary.iter().map(|(_, o)| o).sum::<u32>()),
+                "vocabulary_known_coverage" | "vocabulary_in_progress_coverage" => json!(0),
+                "is_built_in" => json!(!matches!(deck.id, AnyDeckWidget::UserDeckId(_))),
+                _ => Value::Null,
+            })
+            .collect()
+    }
+
+    fn list_decks(decks: &[FakeDeck], body: &Value) -> Result<Value, Failure> {
+        let body: FieldsBody = parse_body(body)?;
+        let fields = check_fields(&body.fields, DECK_FIELDS)?;
+        let decks: Vec<_> = decks
+            .iter()
+            .map(|deck| Self::deck_row(deck, fields))
+            .collect();
+        Ok(json!({ "decks": decks }))
+    }
+
+    fn create_empty_deck(&mut self, body: &Value) -> Result<Value, Failure> {
+        let body: CreateEmptyDeckBody = parse_body(body)?;
+        if body.name.is_empty() {
+            return Err(bad_request("the deck name can't be empty"));
+        }
+        let Some(next_deck_id) = self.next_deck_id.checked_add(1) else {
+            return Err((400, "too_many_decks", String::from("too many decks")));
+        };
+        let id = self.next_deck_id;
+        self.next_deck_id = next_deck_id;
+        let deck = FakeDeck::new(AnyDeckWidget::UserDeckId(id), &body.name);
+        let position = body.position.unwrap_or(usize::MAX);
+        self.user_decks
+            .insert(position.min(self.user_decks.len()), deck);
+        Ok(json!({ "id": id }))
+    }
+
+    fn delete_deck(&mut self, body: &Value) -> Result<Value, Failure> {
+        let body: DeckIdBody = parse_body(body)?;
+        self.deck(&body.id)?;
+        if !matches!(body.id, AnyDeckWidget::UserDeckId(_)) {
+            return Err(bad_request("special decks can't be deleted"));
+        }
+        self.user_decks.retain(|deck| deck.id != body.id);
+        Ok(json!({}))
+    }
+
+    fn rename_deck(&mut self, body: &Value) -> Result<Value, Failure> {
+        let body: RenameDeckBody = parse_body(body)?;
+        if body.name.is_empty() {
+            return Err(bad_request("the deck name can't be empty"));
+        }
+        self.deck_mut(&body.id)?.name = body.name;
+        Ok(json!({}))
+    }
+
+    fn clear_deck(&mut self, body: &Value) -> Result<Value, Failure> {
+        let body: DeckIdBody = parse_body(body)?;
+        self.deck_mut(&body.id)?.vocabulary.clear();
+        Ok(json!({}))
+    }
+
+    fn list_vocabulary(&self, body: &Value) -> Result<Value, Failure> {
+        let body: ListVocabularyBody = parse_body(body)?;
+        let deck = self.deck(&body.id)?;
+        let vocabulary: Vec<_> = deck.vocabulary.iter().map(|(v, _)| *v).collect();
+        if body.fetch_occurences.unwrap_or(false) {
+            let occurences: Vec<_> = deck.vocabulary.iter().map(|(_, o)| *o).collect();
+            Ok(json!({ "vocabulary": vocabulary, "occurences": occurences }))
+        } else {
+            Ok(json!({ "vocabulary": vocabulary, "occurences": null }))
+        }
+    }
+
+    fn add_vocabulary(&mut self, body: &Value) -> Result<Value, Failure> {
+        let body: AddVocabularyBody = parse_body(body)?;
+        self.deck(&body.id)?;
+        if let Some(ref occurences) = body.occurences {
+            if occurences.len() != body.vocabulary.len() {
+                return Err(bad_request(
+                    "occurences must have as many elements as vocabulary",
+                ));
+            }
+        }
+        let mut added = Vec::with_capacity(body.vocabulary.len());
+        for (i, &vocabulary) in body.vocabulary.iter().enumerate() {
+            match self.word(vocabulary) {
+                Ok(_) => {
+                    let occurences = body.occurences.as_ref().map(|o| o[i]);
+                    added.push((vocabulary, occurences));
+                }
+                Err(_) if body.ignore_unknown.unwrap_or(false) => {}
+                Err(failure) => return Err(failure),
+            }
+        }
+        let replace = body.replace_existing_occurences.unwrap_or(false);
+        let deck = self.deck_mut(&body.id)?;
+        for (vocabulary, occurences) in added {
+            match deck.vocabulary.iter_mut().find(|(v, _)| *v == vocabulary) {
+                Some((_, existing)) => match occurences {
+                    Some(o) if replace => *existing = o,
+                    Some(o) => *existing += o,
+                    None => {}
+                },
+                None => deck.vocabulary.push((vocabulary, occurences.unwrap_or(1))),
+            }
+        }
+        Ok(json!({}))
+    }
+
+    fn remove_vocabulary(&mut self, body: &Value) -> Result<Value, Failure> {
+        let body: RemoveVocabularyBody = parse_body(body)?;
+        self.deck_mut(&body.id)?
+            .vocabulary
+            .retain(|(v, _)| !body.vocabulary.contains(v));
+        Ok(json!({}))
+    }
+
+    fn set_card_sentence(&mut self, body: &Value) -> Result<Value, Failure> {
+        let body: SetCardSentenceBody = parse_body(body)?;
+        let vocabulary = Vocabulary(body.vid, body.sid);
+        let spelling = self.word(vocabulary)?.spelling.clone();
+        if let Some(ref sentence) = body.sentence {
+            if !sentence.contains(&spelling) {
+                return Err((
+                    400,
+                    "bad_sentence",
+                    String::from("the vocabulary wasn't found in the sentence"),
+                ));
+            }
+        }
+        let card = self.card_sentences.entry(vocabulary).or_default();
+        if body.sentence.is_some() {
+            card.sentence = body.sentence;
+        }
+        if body.translation.is_some() {
+            card.translation = body.translation;
+        }
+        if body.clear_audio.unwrap_or(false) {
+            card.has_audio = false;
+        }
+        if body.clear_image.unwrap_or(false) {
+            card.has_image = false;
+        }
+        Ok(json!({}))
+    }
+
+    fn lookup_vocabulary(&self, body: &Value) -> Result<Value, Failure> {
+        let body: LookupVocabularyBody = parse_body(body)?;
+        let fields = check_fields(&body.fields, VOCABULARY_FIELDS)?;
+        let info: Vec<_> = body
+            .list
+            .iter()
+            .map(|&vocabulary| match self.word(vocabulary) {
+                Ok(word) => self.vocabulary_row(word, fields),
+                Err(_) => Value::Null,
+            })
+            .collect();
+        Ok(json!({ "vocabulary_info": info }))
+    }
+
+    /// Tokenizes `text` by greedily matching the longest known spelling, adding the words it
+    /// finds to `vocabulary`
+    fn tokenize<'a>(
+        &'a self,
+        text: &str,
+        token_fields: &[String],
+        vocabulary: &mut Vec<&'a FakeWord>,
+    ) -> Vec<Vec<Value>> {
+        let mut tokens = Vec::new();
+        let mut chars = text.char_indices().enumerate();
+        while let Some((position_utf32, (position_utf8, _))) = chars.next() {
+            let rest = &text[position_utf8..];
+            let Some(word) = self
+                .words
+                .iter()
+                .filter(|w| !w.spelling.is_empty() && rest.starts_with(&w.spelling))
+                .max_by_key(|w| w.spelling.len())
+            else {
+                continue;
+            };
+            let index = match vocabulary
+                .iter()
+                .position(|w| w.vocabulary == word.vocabulary)
+            {
+                Some(index) => index,
+                None => {
+                    vocabulary.push(word);
+                    vocabulary.len() - 1
+                }
+            };
+            let length_utf32 = word.spelling.chars().count();
+            let row: Vec<_> = token_fields
+                .iter()
+                .map(|field| match field.as_str() {
+                    "vocabulary_index" => json!(index),
+                    "position_utf8" => json!(position_utf8),
+                    "position_utf32" => json!(position_utf32),
+                    "length_utf8" => json!(word.spelling.len()),
+                    "length_utf32" => json!(length_utf32),
+                    _ if word.spelling == word.reading => Value::Null,
+                    _ => json!([[word.spelling, word.reading]]),
+                })
+                .collect();
+            tokens.push(row);
+            for _ in 1..length_utf32 {
+                chars.next();
+            }
+        }
+        tokens
+    }
+
+    fn parse(&self, body: &Value) -> Result<Value, Failure> {
+        let body: ParseBody = parse_body(body)?;
+        let token_fields = check_fields(&body.token_fields, TOKEN_FIELDS)?;
+        let vocabulary_fields = check_fields(&body.vocabulary_fields, VOCABULARY_FIELDS)?;
+
+        let mut vocabulary: Vec<&FakeWord> = Vec::new();
+        let tokens = match body.text {
+            ParseText::Single(ref text) => {
+                json!(self.tokenize(text, token_fields, &mut vocabulary))
+            }
+            ParseText::Many(ref texts) => {
+                let tokens: Vec<_> = texts
+                    .iter()
+                    .map(|text| self.tokenize(text, token_fields, &mut vocabulary))
+                    .collect();
+                json!(tokens)
+            }
+        };
+        let vocabulary: Vec<_> = vocabulary
+            .iter()
+            .map(|word| self.vocabulary_row(word, vocabulary_fields))
+            .collect();
+        Ok(json!({ "tokens": tokens, "vocabulary": vocabulary }))
+    }
+
+    fn handle(&mut self, path: &str, body: &Value) -> Result<Value, Failure> {
+        match path {
+            "ping" => Ok(json!({})),
+            "parse" => self.parse(body),
+            "lookup-vocabulary" => self.lookup_vocabulary(body),
+            "list-user-decks" => Self::list_decks(&self.user_decks, body),
+            "list-special-decks" => Self::list_decks(&self.special_decks, body),
+            "deck/create-empty" => self.create_empty_deck(body),
+            "deck/delete" => self.delete_deck(body),
+            "deck/rename" => self.rename_deck(body),
+            "deck/clear" => self.clear_deck(body),
+            "deck/list-vocabulary" => self.list_vocabulary(body),
+            "deck/add-vocabulary" => self.add_vocabulary(body),
+            "deck/remove-vocabulary" => self.remove_vocabulary(body),
+            "deck/set-card-sentence" => self.set_card_sentence(body),
+            _ => Err((404, "not_found", format!("unknown endpoint: {path}"))),
+        }
+    }
+}
+
+/// An in-memory jpdb, see the [module documentation](self).
+///
+/// Clones share the same state, so a clone can be handed to a [`Client`] while the original is
+/// used to set up and inspect the fake.
+#[derive(Debug, Clone)]
+pub struct FakeJpdb {
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeJpdb {
+    /// Creates an empty fake, which only accepts `token` as API key
+    pub fn new(token: &str) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                token: token.to_owned(),
+                base_url: DEFAULT_BASE_URL.to_owned(),
+                words: Vec::new(),
+                user_decks: Vec::new(),
+                special_decks: vec![
+                    FakeDeck::new(AnyDeckWidget::Blacklist, "Blacklisted vocabulary"),
+                    FakeDeck::new(AnyDeckWidget::NeverForget, "Never forget"),
+                ],
+                next_deck_id: 1,
+                card_sentences: HashMap::new(),
+                failures: VecDeque::new(),
+                requests: Vec::new(),
+            })),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Serves the API under `base_url` instead of jpdb's, for clients built with
+    /// [`ClientBuilder::base_url`](crate::client::ClientBuilder::base_url). Requests to other URLs
+    /// are answered with a 404.
+    pub fn set_base_url(&self, base_url: &str) {
+        let mut base_url = base_url.to_owned();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        self.state().base_url = base_url;
+    }
+
+    /// A client using the right token and base URL, and sending its requests to this fake
+    pub fn client(&self) -> Client {
+        let state = self.state();
+        let mut client = Client::with_transport(&state.token, self.clone());
+        client.base_url = state.base_url.clone();
+        client
+    }
+
+    /// The async counterpart of [`FakeJpdb::client`]
+    #[cfg(feature = "async")]
+    pub fn async_client(&self) -> AsyncClient {
+        let state = self.state();
+        let mut client = AsyncClient::with_transport(&state.token, self.clone());
+        client.base_url = state.base_url.clone();
+        client
+    }
+
+    /// Adds a word to the dictionary, so that it can be added to decks, parsed and looked up
+    pub fn add_word(&self, word: FakeWord) {
+        self.state().words.push(word);
+    }
+
+    /// Adds a placeholder word for each id, with `Vocabulary(id, id)` as its vid and sid, for tests
+    /// where what the words are doesn't matter
+    pub fn add_words(&self, ids: impl IntoIterator<Item = u32>) {
+        self.state().words.extend(
+            ids.into_iter()
+                .map(|id| FakeWord::new(Vocabulary(id, id), "語", "ご")),
+        );
+    }
+
+    /// Creates a user deck directly, without going through the API.
+    ///
+    /// Panics if the fake already has as many user decks as ids fit in a [`UserDeckId`].
+    pub fn add_deck(&self, name: &str, vocabulary: &[(Vocabulary, u32)]) -> UserDeckId {
+        let mut state = self.state();
+        let id = state.next_deck_id;
+        state.next_deck_id = id.checked_add(1).expect("no user deck id is left");
+        let mut deck = FakeDeck::new(AnyDeckWidget::UserDeckId(id), name);
+        deck.vocabulary = vocabulary.to_vec();
+        state.user_decks.push(deck);
+        UserDeckId(id)
+    }
+
+    /// Names of the user decks, in order
+    pub fn deck_names(&self) -> Vec<String> {
+        self.state()
+            .user_decks
+            .iter()
+            .map(|deck| deck.name.clone())
+            .collect()
+    }
+
+    /// Vocabulary of a deck along with its occurences, or `None` if the deck doesn't exist
+    pub fn deck_vocabulary(&self, deck_id: impl AnyDeckId) -> Option<Vec<(Vocabulary, u32)>> {
+        self.state()
+            .deck(&deck_id.as_any())
+            .ok()
+            .map(|deck| deck.vocabulary.clone())
+    }
+
+    pub fn card_sentence(&self, vocabulary: Vocabulary) -> Option<FakeCardSentence> {
+        self.state().card_sentences.get(&vocabulary).cloned()
+    }
+
+    /// Makes the next request fail with the given status and error, before it's even looked at.
+    ///
+    /// Failures are queued, so this can be called several times in a row.
+    pub fn fail_next(&self, status: u16, error: &str, error_message: &str) {
+        self.state()
+            .failures
+            .push_back((status, error.to_owned(), error_message.to_owned()));
+    }
+
+    /// Every request received so far, including the failed ones
+    pub fn requests(&self) -> Vec<Request> {
+        self.state().requests.clone()
+    }
+}
+
+fn error_response(status: u16, error: &str, error_message: &str) -> HttpResponse {
+    let body = json!({ "error": error, "error_message": error_message });
+    HttpResponse::new(status, body.to_string().into_bytes())
+}
+
+impl Transport for FakeJpdb {
+    fn send(
+        &self,
+        request: &Request,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, TransportError> {
+        let mut state = self.state();
+        state.requests.push(request.clone());
+
+        if let Some((status, error, message)) = state.failures.pop_front() {
+            return Ok(error_response(status, &error, &message));
+        }
+
+        let token = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Authorization"))
+            .and_then(|(_, value)| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if token.is_empty() {
+            return Ok(error_response(403, "bad_key", "missing API key"));
+        }
+        if token != state.token {
+            return Ok(error_response(403, "bad_key", "invalid API key"));
+        }
+
+        let Some(path) = request.url.strip_prefix(state.base_url.as_str()) else {
+            return Ok(error_response(404, "not_found", "unknown endpoint"));
+        };
+        match state.handle(path, &request.body) {
+            Ok(body) => Ok(HttpResponse::new(200, body.to_string().into_bytes())),
+            Err((status, error, message)) => Ok(error_response(status, error, &message)),
+        }
+    }
+}
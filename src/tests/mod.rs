@@ -1 +1,32 @@
+mod tests_api_key;
+mod tests_archive;
+#[cfg(feature = "async")]
+mod tests_async;
+mod tests_batch;
+mod tests_card;
 mod tests_common;
+mod tests_deck_ops;
+mod tests_decode;
+mod tests_endpoint;
+mod tests_error;
+mod tests_furigana;
+mod tests_governor;
+mod tests_parse;
+mod tests_pitch;
+mod tests_sync;
+mod tests_transport;
+
+use crate::{
+    request::{UserDeckId, Vocabulary},
+    testing::FakeJpdb,
+};
+
+/// A fake knowing words 1 to 5, with a user deck for each name and its words
+fn fake_with_decks<const N: usize>(
+    decks: [(&str, &[(Vocabulary, u32)]); N],
+) -> (FakeJpdb, [UserDeckId; N]) {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_words(1..=5);
+    let ids = decks.map(|(name, vocabulary)| fake.add_deck(name, vocabulary));
+    (fake, ids)
+}
@@ -0,0 +1,26 @@
+use crate::{api_key::ApiKey, client::Client, testing::FakeJpdb};
+
+#[test]
+fn api_key_redacted() {
+    let client = Client::new("very_secret_key");
+    assert!(!format!("{client}").contains("very_secret_key"));
+    assert!(!format!("{client:?}").contains("very_secret_key"));
+    assert_eq!(format!("{}", ApiKey::new("very_secret_key")), "[redacted]");
+}
+
+#[test]
+fn api_key_from_file() {
+    let path = std::env::temp_dir().join(format!("jpdb-api-key-{}", std::process::id()));
+    std::fs::write(&path, "aaa\n").unwrap();
+    let key = ApiKey::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let client = Client::with_transport(key.unwrap(), FakeJpdb::new("aaa"));
+    assert!(client.ping().is_ok());
+}
+
+#[test]
+fn api_key_from_missing_env_var() {
+    let key = ApiKey::from_env_var("JPDB_SURELY_NOT_SET");
+    assert_eq!(key.unwrap_err(), std::env::VarError::NotPresent);
+}
@@ -0,0 +1,160 @@
+use crate::{
+    archive::{
+        AccountArchive, ConflictPolicy, ImportAction, ImportLog, ImportOptions, ARCHIVE_VERSION,
+    },
+    error::Error,
+    request::{AddVocabularyOptions, AnyDeckWidget, SpecialDeckId, Vocabulary},
+    testing::FakeJpdb,
+};
+
+fn fake_with_words() -> FakeJpdb {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_words(1..=4);
+    fake
+}
+
+/// An account with two user decks and a word in the never-forget deck
+fn exported() -> AccountArchive {
+    let fake = fake_with_words();
+    fake.add_deck("books", &[(Vocabulary(1, 1), 3), (Vocabulary(2, 2), 1)]);
+    fake.add_deck("anime", &[(Vocabulary(3, 3), 2)]);
+    let client = fake.client();
+    client
+        .add_vocabulary(
+            SpecialDeckId::NeverForget,
+            &AddVocabularyOptions {
+                vocabulary: &[Vocabulary(4, 4)],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    client.export_account().unwrap()
+}
+
+#[test]
+fn export_account() {
+    let archive = exported();
+    assert_eq!(archive.version, ARCHIVE_VERSION);
+    let names: Vec<_> = archive.decks.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["books", "anime", "Blacklisted vocabulary", "Never forget"]
+    );
+    assert_eq!(archive.decks[0].position, Some(0));
+    assert_eq!(archive.decks[1].position, Some(1));
+    assert_eq!(archive.decks[3].position, None);
+    assert_eq!(archive.decks[0].occurences, [3, 1]);
+    assert_eq!(archive.decks[3].vocabulary, [Vocabulary(4, 4)]);
+
+    let json = archive.to_json().unwrap();
+    assert_eq!(AccountArchive::from_json(&json).unwrap(), archive);
+}
+
+#[test]
+fn newer_archive_is_rejected() {
+    let json = format!("{{\"version\": {}, \"decks\": []}}", ARCHIVE_VERSION + 1);
+    assert!(matches!(
+        AccountArchive::from_json(&json),
+        Err(Error::BadArchive(_))
+    ));
+}
+
+#[test]
+fn import_into_fresh_account() {
+    let archive = exported();
+    let fake = fake_with_words();
+    let mut log = ImportLog::default();
+    fake.client()
+        .import_account(&archive, ImportOptions::default(), &mut log)
+        .unwrap();
+
+    assert_eq!(fake.deck_names(), ["books", "anime"]);
+    assert_eq!(
+        fake.deck_vocabulary(AnyDeckWidget::UserDeckId(1)).unwrap(),
+        [(Vocabulary(1, 1), 3), (Vocabulary(2, 2), 1)]
+    );
+    assert_eq!(
+        fake.deck_vocabulary(SpecialDeckId::NeverForget).unwrap(),
+        [(Vocabulary(4, 4), 1)]
+    );
+    let actions: Vec<_> = log.decks.iter().map(|d| d.action).collect();
+    assert_eq!(
+        actions,
+        [
+            ImportAction::Created,
+            ImportAction::Created,
+            ImportAction::Merged,
+            ImportAction::Merged,
+        ]
+    );
+    assert!(log.decks.iter().all(|d| d.done));
+}
+
+#[test]
+fn import_conflicts() {
+    let archive = exported();
+    let import = |policy| {
+        let fake = fake_with_words();
+        let books = fake.add_deck("books", &[(Vocabulary(1, 1), 1), (Vocabulary(3, 3), 1)]);
+        fake.client()
+            .import_account(
+                &archive,
+                ImportOptions {
+                    on_conflict: policy,
+                },
+                &mut ImportLog::default(),
+            )
+            .unwrap();
+        assert_eq!(fake.deck_names(), ["books", "anime"]);
+        fake.deck_vocabulary(books).unwrap()
+    };
+
+    assert_eq!(
+        import(ConflictPolicy::Skip),
+        [(Vocabulary(1, 1), 1), (Vocabulary(3, 3), 1)]
+    );
+    assert_eq!(
+        import(ConflictPolicy::Merge),
+        [
+            (Vocabulary(1, 1), 3),
+            (Vocabulary(3, 3), 1),
+            (Vocabulary(2, 2), 1)
+        ]
+    );
+    assert_eq!(
+        import(ConflictPolicy::Replace),
+        [(Vocabulary(1, 1), 3), (Vocabulary(2, 2), 1)]
+    );
+}
+
+#[test]
+fn import_resumes_from_log() {
+    let archive = exported();
+    // Word 3 is missing, so adding the words of "anime" fails after it was created
+    let fake = FakeJpdb::new("aaa");
+    fake.add_words([1, 2, 4]);
+    let client = fake.client();
+    let mut log = ImportLog::default();
+    let result = client.import_account(&archive, ImportOptions::default(), &mut log);
+    assert!(result.is_err());
+    assert_eq!(log.decks.len(), 2);
+    assert!(log.decks[0].done && !log.decks[1].done);
+
+    fake.add_words([3]);
+    client
+        .import_account(&archive, ImportOptions::default(), &mut log)
+        .unwrap();
+    assert_eq!(fake.deck_names(), ["books", "anime"]);
+    assert_eq!(
+        fake.deck_vocabulary(&log.decks[1].id).unwrap(),
+        [(Vocabulary(3, 3), 2)]
+    );
+    assert!(log.decks.iter().all(|d| d.done));
+
+    let mut other = exported();
+    other.decks[0].name = String::from("manga");
+    assert!(matches!(
+        client.import_account(&other, ImportOptions::default(), &mut log),
+        Err(Error::BadArchive(_))
+    ));
+}
@@ -0,0 +1,324 @@
+use std::{future::Future, time::Duration};
+
+use serde_json::json;
+
+use crate::{
+    archive::{ImportLog, ImportOptions},
+    async_client::AsyncClient,
+    deck_ops::{DeckTarget, SetOperation},
+    error::{Error, ErrorKind},
+    governor::{RateLimit, RetryPolicy},
+    request::{
+        AddVocabularyOptions, AnyDeckId, DeckQueryField, SetCardSentenceOptions, Sid,
+        SpecialDeckId, TokenQueryField, UserDeckId, Vid, VocabQueryField, Vocabulary,
+    },
+    sync::SyncOptions,
+    testing::{FakeJpdb, FakeWord},
+};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// A fake with two words in its dictionary, and a single user deck containing the first one
+fn fake() -> (FakeJpdb, AsyncClient, UserDeckId) {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    fake.add_word(FakeWord::new(Vocabulary(13, 13), "の", "の"));
+    let deck = fake.add_deck("deck", &[(Vocabulary(12, 12), 1)]);
+    let client = fake.async_client();
+    (fake, client, deck)
+}
+
+#[test]
+fn async_ping() {
+    let (fake, client, _) = fake();
+    block_on(client.ping()).unwrap();
+    let other = AsyncClient::with_transport("bbb", fake.clone());
+    assert_eq!(
+        block_on(other.ping()).unwrap_err().kind(),
+        ErrorKind::BadKey
+    );
+    let raw = block_on(client.call_raw("/ping", &json!({}))).unwrap();
+    assert_eq!(raw, json!({}));
+    assert_eq!(fake.requests().len(), 3);
+}
+
+#[test]
+fn async_builder() {
+    let fake = FakeJpdb::new("aaa");
+    fake.set_base_url("http://localhost:1234/api/v1");
+    let client = AsyncClient::builder("aaa")
+        .base_url("http://localhost:1234/api/v1")
+        .transport(fake.clone())
+        .retry(RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            ..Default::default()
+        })
+        .rate_limit(RateLimit::per_second(1000))
+        .build()
+        .unwrap();
+    fake.fail_next(429, "too_many_requests", "slow down");
+    block_on(client.ping()).unwrap();
+    let requests = fake.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].url, "http://localhost:1234/api/v1/ping");
+}
+
+#[test]
+fn async_parse() {
+    let (_, client, _) = fake();
+    let result = block_on(client.parse_text(
+        "死神の",
+        &[TokenQueryField::PositionUtf8],
+        Some(&[VocabQueryField::Spelling]),
+    ))
+    .unwrap();
+    assert_eq!(result.tokens.len(), 2);
+
+    let result = block_on(client.parse_long_text("死神の。の死神", &[], None, 9)).unwrap();
+    assert_eq!(result.tokens.len(), 4);
+    assert_eq!(result.vocabulary.len(), 2);
+
+    let result = block_on(client.parse_texts(&["死神", "の"], &[], None, 1000)).unwrap();
+    assert_eq!(result.tokens.len(), 2);
+    assert_eq!(result.vocabulary.len(), 2);
+}
+
+#[test]
+fn async_lookup_vocabulary() {
+    let (_, client, _) = fake();
+    let list = [(Vid(12), Sid(12))];
+    let entries = block_on(client.lookup_vocabulary(&list, &[VocabQueryField::Reading])).unwrap();
+    let entry = entries[&list[0]].as_ref().unwrap();
+    assert_eq!(entry.reading.as_deref(), Some("しにがみ"));
+}
+
+#[test]
+fn async_decks() {
+    let (fake, client, deck) = fake();
+    let created = block_on(client.create_empty_deck("other", None)).unwrap();
+    block_on(client.rename_deck(created, "renamed")).unwrap();
+    assert_eq!(fake.deck_names(), ["deck", "renamed"]);
+
+    let decks = block_on(client.list_all_decks(&[DeckQueryField::Name])).unwrap();
+    assert_eq!(decks.len(), 4);
+    assert_eq!(decks[1].1.name.as_deref(), Some("renamed"));
+
+    block_on(client.clear_deck(deck)).unwrap();
+    assert_eq!(fake.deck_vocabulary(deck), Some(vec![]));
+    block_on(client.delete_deck(created)).unwrap();
+    assert_eq!(fake.deck_names(), ["deck"]);
+    assert!(matches!(
+        block_on(client.create_empty_deck("", None)),
+        Err(Error::InvalidRequest("name", _))
+    ));
+}
+
+#[test]
+fn async_vocabulary() {
+    let (fake, _, deck) = fake();
+    let client = AsyncClient::builder("aaa")
+        .transport(fake.clone())
+        .chunk_size(1)
+        .build()
+        .unwrap();
+    let mut chunks = Vec::new();
+    block_on(client.add_vocabulary_with_progress(
+        deck,
+        &AddVocabularyOptions {
+            vocabulary: &[Vocabulary(12, 12), Vocabulary(13, 13)],
+            occurences: Some(&[2, 3]),
+            overwrite_occurences: Some(true),
+            ..Default::default()
+        },
+        |progress| chunks.push(progress.chunk),
+    ))
+    .unwrap();
+    assert_eq!(chunks, [1, 2]);
+    let occurences = block_on(client.list_vocabulary_with_occurences(deck)).unwrap();
+    assert_eq!(occurences[&Vocabulary(13, 13)], 3);
+
+    block_on(client.remove_vocabulary(deck, &[Vocabulary(12, 12)])).unwrap();
+    assert_eq!(
+        block_on(client.list_vocabulary(deck)).unwrap(),
+        [Vocabulary(13, 13)]
+    );
+}
+
+#[test]
+fn async_vocabulary_partial_failure() {
+    let (fake, _, deck) = fake();
+    let client = AsyncClient::builder("aaa")
+        .transport(fake.clone())
+        .chunk_size(1)
+        .build()
+        .unwrap();
+    let resp = block_on(client.add_vocabulary(
+        deck,
+        &AddVocabularyOptions {
+            vocabulary: &[Vocabulary(13, 13), Vocabulary(42, 42)],
+            ..Default::default()
+        },
+    ));
+    match resp {
+        Err(Error::PartialBatch(applied, error)) => {
+            assert_eq!(applied, [Vocabulary(13, 13)]);
+            assert_eq!(error.kind(), ErrorKind::BadVid);
+        }
+        other => panic!("expected a partial batch, got {other:?}"),
+    }
+
+    fake.fail_next(503, "api_unavailable", "down");
+    let resp = block_on(client.remove_vocabulary(deck, &[Vocabulary(12, 12), Vocabulary(13, 13)]));
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::ApiUnavailable);
+    assert_eq!(fake.deck_vocabulary(deck).unwrap().len(), 2);
+}
+
+#[test]
+fn async_set_card_sentence() {
+    let (fake, client, _) = fake();
+    block_on(client.set_card_sentence(&SetCardSentenceOptions {
+        vid: Vid(12),
+        sid: Sid(12),
+        sentence: Some("死神の本"),
+        ..Default::default()
+    }))
+    .unwrap();
+    let card = fake.card_sentence(Vocabulary(12, 12)).unwrap();
+    assert_eq!(card.sentence.as_deref(), Some("死神の本"));
+}
+
+#[test]
+fn async_sync_deck() {
+    let (fake, client, deck) = fake();
+    let report =
+        block_on(client.sync_deck(deck, &[(Vocabulary(13, 13), 2)], SyncOptions::default()))
+            .unwrap();
+    assert_eq!(report.added, [Vocabulary(13, 13)]);
+    assert_eq!(report.removed, [Vocabulary(12, 12)]);
+    assert_eq!(
+        fake.deck_vocabulary(deck),
+        Some(vec![(Vocabulary(13, 13), 2)])
+    );
+}
+
+#[test]
+fn async_sync_deck_failure() {
+    let (fake, client, deck) = fake();
+    fake.fail_next(503, "api_unavailable", "down");
+    let resp = block_on(client.sync_deck(deck, &[(Vocabulary(13, 13), 2)], SyncOptions::default()));
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::ApiUnavailable);
+    assert_eq!(
+        fake.deck_vocabulary(deck),
+        Some(vec![(Vocabulary(12, 12), 1)])
+    );
+}
+
+#[test]
+fn async_archive() {
+    let (_, client, _) = fake();
+    let archive = block_on(client.export_account()).unwrap();
+    assert_eq!(archive.decks.len(), 3);
+
+    let (other, _, _) = fake();
+    let mut log = ImportLog::default();
+    block_on(
+        other
+            .async_client()
+            .import_account(&archive, ImportOptions::default(), &mut log),
+    )
+    .unwrap();
+    assert!(log.decks.iter().all(|deck| deck.done));
+    assert_eq!(other.deck_names(), ["deck"]);
+}
+
+#[test]
+fn async_import_resumes_from_log() {
+    let (_, client, _) = fake();
+    let archive = block_on(client.export_account()).unwrap();
+    // Word 12 is missing, so adding the words of "deck" fails after it was created
+    let other = FakeJpdb::new("aaa");
+    let client = other.async_client();
+    let mut log = ImportLog::default();
+    let resp = block_on(client.import_account(&archive, ImportOptions::default(), &mut log));
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::BadVid);
+    assert!(!log.decks[0].done);
+
+    other.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    block_on(client.import_account(&archive, ImportOptions::default(), &mut log)).unwrap();
+    assert_eq!(other.deck_names(), ["deck"]);
+    assert_eq!(
+        other.deck_vocabulary(&log.decks[0].id),
+        Some(vec![(Vocabulary(12, 12), 1)])
+    );
+
+    let mut renamed = archive.clone();
+    renamed.decks[0].name = String::from("renamed");
+    assert!(matches!(
+        block_on(client.import_account(&renamed, ImportOptions::default(), &mut log)),
+        Err(Error::BadArchive(_))
+    ));
+}
+
+#[test]
+fn async_deck_ops() {
+    let (fake, client, deck) = fake();
+    let id = block_on(client.combine_decks(
+        SetOperation::Union,
+        &[deck.as_any()],
+        DeckTarget::New {
+            name: "copy",
+            position: None,
+        },
+    ))
+    .unwrap();
+    assert_eq!(
+        fake.deck_vocabulary(id),
+        Some(vec![(Vocabulary(12, 12), 1)])
+    );
+
+    block_on(client.move_vocabulary(deck, SpecialDeckId::NeverForget, &[Vocabulary(12, 12)]))
+        .unwrap();
+    assert_eq!(fake.deck_vocabulary(deck), Some(vec![]));
+    block_on(client.merge_decks(SpecialDeckId::NeverForget, deck)).unwrap();
+    assert_eq!(
+        fake.deck_vocabulary(deck),
+        Some(vec![(Vocabulary(12, 12), 1)])
+    );
+}
+
+#[test]
+fn async_deck_ops_failures() {
+    let (fake, client, deck) = fake();
+    assert!(matches!(
+        block_on(client.combine_decks(
+            SetOperation::Union,
+            &[],
+            DeckTarget::Existing(deck.as_any())
+        )),
+        Err(Error::InvalidRequest("decks", _))
+    ));
+    assert!(matches!(
+        block_on(client.move_vocabulary(deck, SpecialDeckId::NeverForget, &[Vocabulary(13, 13)])),
+        Err(Error::InvalidRequest("vocabulary", _))
+    ));
+    assert!(matches!(
+        block_on(client.merge_decks(deck, deck)),
+        Err(Error::InvalidRequest("to", _))
+    ));
+
+    fake.fail_next(503, "api_unavailable", "down");
+    let resp = block_on(client.merge_decks(deck, SpecialDeckId::NeverForget));
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::ApiUnavailable);
+    assert_eq!(
+        fake.deck_vocabulary(deck),
+        Some(vec![(Vocabulary(12, 12), 1)])
+    );
+}
@@ -0,0 +1,143 @@
+use super::fake_with_decks;
+use crate::{
+    batch::ChunkProgress,
+    client::Client,
+    error::{Error, ErrorKind},
+    request::{AddVocabularyOptions, UserDeckId, Vocabulary},
+    testing::FakeJpdb,
+};
+
+/// A fake knowing words 1 to 5, with an empty deck, and a client sending two words per request
+fn fake() -> (FakeJpdb, Client, UserDeckId) {
+    let (fake, [deck]) = fake_with_decks([("deck", &[])]);
+    let client = Client::builder("aaa")
+        .transport(fake.clone())
+        .chunk_size(2)
+        .build()
+        .unwrap();
+    (fake, client, deck)
+}
+
+fn words(range: std::ops::RangeInclusive<u32>) -> Vec<Vocabulary> {
+    range.map(|i| Vocabulary(i, i)).collect()
+}
+
+#[test]
+fn add_vocabulary_in_chunks() {
+    let (fake, client, deck) = fake();
+    let vocabulary = words(1..=5);
+    let mut progress = Vec::new();
+    client
+        .add_vocabulary_with_progress(
+            deck,
+            &AddVocabularyOptions {
+                vocabulary: &vocabulary,
+                occurences: Some(&[1, 2, 3, 4, 5]),
+                ..Default::default()
+            },
+            |p| progress.push(p),
+        )
+        .unwrap();
+
+    assert_eq!(fake.requests().len(), 3);
+    let expected: Vec<_> = (1..=5).map(|i| (Vocabulary(i, i), i)).collect();
+    assert_eq!(fake.deck_vocabulary(deck), Some(expected));
+    assert_eq!(
+        progress,
+        vec![
+            ChunkProgress {
+                chunk: 1,
+                chunks: 3,
+                done: 2,
+                total: 5
+            },
+            ChunkProgress {
+                chunk: 2,
+                chunks: 3,
+                done: 4,
+                total: 5
+            },
+            ChunkProgress {
+                chunk: 3,
+                chunks: 3,
+                done: 5,
+                total: 5
+            },
+        ]
+    );
+}
+
+#[test]
+fn add_vocabulary_reports_partial_failure() {
+    let (fake, client, deck) = fake();
+    let mut vocabulary = words(1..=3);
+    vocabulary.push(Vocabulary(42, 42));
+    let resp = client.add_vocabulary(
+        deck,
+        &AddVocabularyOptions {
+            vocabulary: &vocabulary,
+            ..Default::default()
+        },
+    );
+    match resp {
+        Err(Error::PartialBatch(applied, error)) => {
+            assert_eq!(applied, words(1..=2));
+            assert_eq!(error.kind(), ErrorKind::BadVid);
+        }
+        other => panic!("expected a partial batch, got {other:?}"),
+    }
+    assert_eq!(fake.deck_vocabulary(deck).unwrap().len(), 2);
+}
+
+#[test]
+fn add_vocabulary_first_chunk_failure_is_unwrapped() {
+    let (_, client, deck) = fake();
+    let resp = client.add_vocabulary(
+        deck,
+        &AddVocabularyOptions {
+            vocabulary: &[Vocabulary(42, 42)],
+            ..Default::default()
+        },
+    );
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::BadVid);
+}
+
+#[test]
+fn add_vocabulary_validates_whole_batch() {
+    let (fake, client, deck) = fake();
+    let vocabulary = words(1..=5);
+    let resp = client.add_vocabulary(
+        deck,
+        &AddVocabularyOptions {
+            vocabulary: &vocabulary,
+            occurences: Some(&[1, 2, 3]),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(resp, Err(Error::InvalidRequest("occurences", _))));
+    assert!(fake.requests().is_empty());
+}
+
+#[test]
+fn remove_vocabulary_in_chunks() {
+    let (fake, client, deck) = fake();
+    let vocabulary = words(1..=5);
+    client
+        .add_vocabulary(
+            deck,
+            &AddVocabularyOptions {
+                vocabulary: &vocabulary,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let mut chunks = 0;
+    client
+        .remove_vocabulary_with_progress(deck, &vocabulary[..4], |p| chunks = p.chunks)
+        .unwrap();
+    assert_eq!(chunks, 2);
+    assert_eq!(
+        fake.deck_vocabulary(deck),
+        Some(vec![(Vocabulary(5, 5), 1)])
+    );
+}
@@ -0,0 +1,94 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::{
+    card::{CardState, DueAt},
+    request::{
+        decode_row, AddVocabularyOptions, Sid, SpecialDeckId, Vid, VocabQueryField, Vocabulary,
+        VocabularyEntry,
+    },
+    testing::{FakeJpdb, FakeWord},
+};
+
+#[test]
+fn card_states_round_trip() {
+    let states: Vec<CardState> = serde_json::from_value(json!([
+        "new",
+        "learning",
+        "known",
+        "never-forget",
+        "due",
+        "failed",
+        "locked",
+        "redundant",
+        "suspended",
+        "blacklisted",
+        "shiny",
+    ]))
+    .unwrap();
+    assert_eq!(states[3], CardState::NeverForget);
+    assert_eq!(states[10], CardState::Unknown(String::from("shiny")));
+    let back: Vec<_> = states.iter().map(CardState::as_str).collect();
+    assert_eq!(serde_json::to_value(&states).unwrap(), json!(back));
+    assert!(states[2].is_known() && states[3].is_known());
+    assert!(!states[1].is_known());
+}
+
+#[test]
+fn decode_due_at() {
+    let entry: VocabularyEntry = decode_row(
+        0,
+        &[VocabQueryField::CardState, VocabQueryField::DueAt],
+        vec![json!(["due", "learning"]), json!(1700000000)],
+    )
+    .unwrap();
+    assert_eq!(
+        entry.card_state,
+        Some(vec![CardState::Due, CardState::Learning])
+    );
+    let due_at = entry.due_at.unwrap();
+    assert_eq!(due_at, DueAt(1700000000));
+    assert_eq!(
+        due_at.to_system_time(),
+        Some(UNIX_EPOCH + Duration::from_secs(1700000000))
+    );
+    assert_eq!(DueAt(u64::MAX).to_system_time(), None);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn due_at_to_chrono() {
+    let date = DueAt(1700000000).to_chrono().unwrap();
+    assert_eq!(date.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn due_at_to_time() {
+    let date = DueAt(1700000000).to_offset_date_time().unwrap();
+    assert_eq!(date.unix_timestamp(), 1700000000);
+    assert_eq!(date.year(), 2023);
+}
+
+#[test]
+fn lookup_card_state() {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    let client = fake.client();
+    client
+        .add_vocabulary(
+            SpecialDeckId::NeverForget,
+            &AddVocabularyOptions {
+                vocabulary: &[Vocabulary(12, 12)],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let list = [(Vid(12), Sid(12))];
+    let entries = client
+        .lookup_vocabulary(&list, &[VocabQueryField::CardState])
+        .unwrap();
+    let entry = entries[&list[0]].as_ref().unwrap();
+    assert_eq!(entry.card_state, Some(vec![CardState::NeverForget]));
+}
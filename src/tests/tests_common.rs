@@ -1,69 +1,123 @@
 use crate::{
+    card::CardState,
     client::Client,
+    error::{Error, ErrorKind},
     request::{
-        AddVocabularyOptions, DeckQueryField, DeckVocabulary, SetCardSentenceOptions, Sid,
+        AddVocabularyOptions, AnyDeckWidget, DeckQueryField, SetCardSentenceOptions, Sid,
         SpecialDeckId, TokenQueryField, UserDeckId, Vid, VocabQueryField, Vocabulary,
     },
+    testing::{FakeJpdb, FakeWord},
 };
 
+/// A fake with two words in its dictionary, and a single user deck containing the first one
+fn fake() -> (FakeJpdb, Client) {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    fake.add_word(FakeWord::new(Vocabulary(13, 13), "の", "の"));
+    fake.add_deck("deck", &[(Vocabulary(12, 12), 1)]);
+    let client = fake.client();
+    (fake, client)
+}
+
 #[test]
 fn post_ping_ok() {
-    let client = Client::new_mock("aaa", Some(String::from("code=200")));
+    let (_, client) = fake();
     let resp = client.ping();
     assert!(resp.is_ok());
 }
 
+#[test]
+fn mock_ping_bad_key() {
+    let (fake, _) = fake();
+    let client = Client::with_transport("bbb", fake);
+    let resp = client.ping();
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::BadKey);
+}
+
+#[test]
+fn mock_ping_missing_key() {
+    let (fake, _) = fake();
+    let client = Client::with_transport("", fake);
+    let resp = client.ping();
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::MissingKey);
+}
+
 #[test]
 fn mock_clear_deck_blacklist() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.clear_deck(SpecialDeckId::Blacklist);
     assert!(resp.is_ok());
 }
 
 #[test]
 fn mock_clear_deck_never_forget() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.clear_deck(SpecialDeckId::NeverForget);
     assert!(resp.is_ok());
 }
 
+#[test]
+fn mock_clear_deck_user() {
+    let (fake, client) = fake();
+    let resp = client.clear_deck(UserDeckId(1));
+    assert!(resp.is_ok());
+    assert_eq!(fake.deck_vocabulary(UserDeckId(1)), Some(vec![]));
+}
+
 #[test]
 fn mock_delete_deck() {
-    let client = Client::new_mock("aaa", None);
+    let (fake, client) = fake();
     let resp = client.delete_deck(UserDeckId(1));
     assert!(resp.is_ok());
+    assert_eq!(fake.deck_vocabulary(UserDeckId(1)), None);
+}
+
+#[test]
+fn mock_delete_deck_bad_deck() {
+    let (_, client) = fake();
+    let resp = client.delete_deck(UserDeckId(2));
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::BadDeck);
 }
 
 #[test]
 fn mock_rename_deck() {
-    let client = Client::new_mock("aaa", None);
+    let (fake, client) = fake();
     let resp = client.rename_deck(UserDeckId(1), "asa");
     assert!(resp.is_ok());
+    assert_eq!(fake.deck_names(), vec![String::from("asa")]);
+}
+
+#[test]
+fn mock_rename_deck_no_name() {
+    let (fake, client) = fake();
+    let resp = client.rename_deck(UserDeckId(1), "");
+    assert!(matches!(resp, Err(Error::InvalidRequest("name", _))));
+    assert!(fake.requests().is_empty());
 }
 
-//Ignored until the update that fixes the endpoint url
-#[ignore]
 #[test]
 fn mock_set_card_sentence() {
-    let client = Client::new_mock("aaa", None);
+    let (fake, client) = fake();
     let options = SetCardSentenceOptions {
-        sentence: Some(""),
+        vid: Vid(12),
+        sid: Sid(12),
+        sentence: Some("babawo死神"),
         translation: Some(""),
         clear_audio: Some(false),
         clear_image: Some(false),
-        ..Default::default()
     };
     let resp = client.set_card_sentence(&options);
-    dbg!(&resp);
     assert!(resp.is_ok());
+    let card = fake.card_sentence(Vocabulary(12, 12)).unwrap();
+    assert_eq!(card.sentence.as_deref(), Some("babawo死神"));
 }
 
-//Ignored until the update that fixes the endpoint url
-#[ignore]
 #[test]
 fn mock_set_card_sentence_none() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let options = SetCardSentenceOptions {
+        vid: Vid(12),
+        sid: Sid(12),
         ..Default::default()
     };
     let resp = client.set_card_sentence(&options);
@@ -72,14 +126,15 @@ fn mock_set_card_sentence_none() {
 
 #[test]
 fn mock_remove_vocabulary() {
-    let client = Client::new_mock("aaa", None);
+    let (fake, client) = fake();
     let resp = client.remove_vocabulary(UserDeckId(1), &[Vocabulary(12, 12), Vocabulary(13, 13)]);
     assert!(resp.is_ok());
+    assert_eq!(fake.deck_vocabulary(UserDeckId(1)), Some(vec![]));
 }
 
 #[test]
 fn mock_remove_neverforget() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.remove_vocabulary(
         SpecialDeckId::NeverForget,
         &[Vocabulary(12, 12), Vocabulary(13, 13)],
@@ -89,7 +144,7 @@ fn mock_remove_neverforget() {
 
 #[test]
 fn mock_remove_blacklist() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.remove_vocabulary(
         SpecialDeckId::Blacklist,
         &[Vocabulary(12, 12), Vocabulary(13, 13)],
@@ -99,7 +154,7 @@ fn mock_remove_blacklist() {
 
 #[test]
 fn mock_add_vocab_blacklist() {
-    let client = Client::new_mock("aaa", None);
+    let (fake, client) = fake();
     let resp = client.add_vocabulary(
         SpecialDeckId::Blacklist,
         &AddVocabularyOptions {
@@ -108,43 +163,113 @@ fn mock_add_vocab_blacklist() {
         },
     );
     assert!(resp.is_ok());
+    assert_eq!(
+        fake.deck_vocabulary(SpecialDeckId::Blacklist),
+        Some(vec![(Vocabulary(12, 12), 1), (Vocabulary(13, 13), 1)])
+    );
 }
 
 #[test]
 fn mock_add_vocab_user() {
-    let client = Client::new_mock("aaa", None);
+    let (fake, client) = fake();
     let resp = client.add_vocabulary(
-        UserDeckId(12),
+        UserDeckId(1),
         &AddVocabularyOptions {
-            vocabulary: &[Vocabulary(13, 13)],
+            vocabulary: &[Vocabulary(12, 12), Vocabulary(13, 13)],
             occurences: Some(&[1, 1]),
             overwrite_occurences: Some(true),
             ignore_unknown: Some(false),
         },
     );
     assert!(resp.is_ok());
+    assert_eq!(
+        fake.deck_vocabulary(UserDeckId(1)),
+        Some(vec![(Vocabulary(12, 12), 1), (Vocabulary(13, 13), 1)])
+    );
+}
+
+#[test]
+fn mock_add_vocab_occurences_mismatch() {
+    let (fake, client) = fake();
+    let resp = client.add_vocabulary(
+        UserDeckId(1),
+        &AddVocabularyOptions {
+            vocabulary: &[Vocabulary(13, 13)],
+            occurences: Some(&[1, 1]),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(resp, Err(Error::InvalidRequest("occurences", _))));
+    assert!(fake.requests().is_empty());
+}
+
+#[test]
+fn mock_add_vocab_unknown() {
+    let (_, client) = fake();
+    let options = AddVocabularyOptions {
+        vocabulary: &[Vocabulary(12, 1)],
+        ..Default::default()
+    };
+    let resp = client.add_vocabulary(UserDeckId(1), &options);
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::BadSid);
+
+    let resp = client.add_vocabulary(
+        UserDeckId(1),
+        &AddVocabularyOptions {
+            ignore_unknown: Some(true),
+            ..options
+        },
+    );
+    assert!(resp.is_ok());
 }
 
 #[test]
 fn mock_create_deck() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.create_empty_deck("baba", None);
-    dbg!(&resp);
-    assert!(resp.is_ok());
-    assert_eq!(resp.unwrap(), UserDeckId(0))
+    assert_eq!(resp.unwrap(), UserDeckId(2))
 }
 
 #[test]
 fn mock_create_deck_some() {
-    let client = Client::new_mock("aaa", None);
-    let resp = client.create_empty_deck("baba", Some(1));
-    assert!(resp.is_ok());
-    assert_eq!(resp.unwrap(), UserDeckId(0))
+    let (fake, client) = fake();
+    let resp = client.create_empty_deck("baba", Some(0));
+    assert_eq!(resp.unwrap(), UserDeckId(2));
+    assert_eq!(
+        fake.deck_names(),
+        vec![String::from("baba"), String::from("deck")]
+    );
+}
+
+#[test]
+fn mock_create_deck_no_name() {
+    let (fake, client) = fake();
+    let resp = client.create_empty_deck("", None);
+    assert!(matches!(resp, Err(Error::InvalidRequest("name", _))));
+    assert!(fake.requests().is_empty());
+    // Whether jpdb accepts blank names isn't documented, so they're sent
+    assert!(client.create_empty_deck(" ", None).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "no user deck id is left")]
+fn mock_add_deck_out_of_ids() {
+    let (fake, _) = fake();
+    for _ in 0..u8::MAX {
+        fake.add_deck("deck", &[]);
+    }
+}
+
+#[test]
+fn mock_list_vocabulary_with_occurences() {
+    let (_, client) = fake();
+    let resp = client.list_vocabulary_with_occurences(UserDeckId(1));
+    assert_eq!(resp.unwrap().get(&Vocabulary(12, 12)), Some(&1));
 }
 
 #[test]
 fn mock_list_special_decks() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.list_special_decks(&[
         DeckQueryField::KnownCoverage,
         DeckQueryField::Id,
@@ -154,12 +279,14 @@ fn mock_list_special_decks() {
         DeckQueryField::WordCount,
         DeckQueryField::IsBuiltIn,
     ]);
-    assert!(resp.is_ok());
+    let decks = resp.unwrap();
+    assert_eq!(decks.len(), 2);
+    assert!(decks.iter().all(|deck| deck.is_built_in == Some(true)));
 }
 
 #[test]
 fn mock_list_user_decks() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.list_user_decks(&[
         DeckQueryField::Id,
         DeckQueryField::Id,
@@ -175,13 +302,17 @@ fn mock_list_user_decks() {
         DeckQueryField::VocabularyCount,
         DeckQueryField::WordCount,
     ]);
-    assert!(resp.is_ok());
+    let decks = resp.unwrap();
+    assert_eq!(decks.len(), 1);
+    assert_eq!(decks[0].id, Some(AnyDeckWidget::UserDeckId(1)));
+    assert_eq!(decks[0].name.as_deref(), Some("deck"));
+    assert_eq!(decks[0].vocabulary_count, Some(1));
 }
 
 #[test]
 fn mock_list_all_decks() {
-    let client = Client::new_mock("aaa", None);
-    let resp = client.list_user_decks(&[
+    let (_, client) = fake();
+    let resp = client.list_all_decks(&[
         DeckQueryField::Id,
         DeckQueryField::InProgressCoverage,
         DeckQueryField::IsBuiltIn,
@@ -190,14 +321,22 @@ fn mock_list_all_decks() {
         DeckQueryField::VocabularyCount,
         DeckQueryField::WordCount,
     ]);
-    assert!(resp.is_ok());
+    let ids: Vec<_> = resp.unwrap().into_iter().map(|(id, _)| id).collect();
+    assert_eq!(
+        ids,
+        vec![
+            AnyDeckWidget::UserDeckId(1),
+            AnyDeckWidget::Blacklist,
+            AnyDeckWidget::NeverForget
+        ]
+    );
 }
 
 #[test]
 fn mock_lookup_vocab() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.lookup_vocabulary(
-        &[(Vid(0), Sid(0))],
+        &[(Vid(12), Sid(12)), (Vid(0), Sid(0))],
         &[
             VocabQueryField::CardLevel,
             VocabQueryField::CardState,
@@ -211,14 +350,18 @@ fn mock_lookup_vocab() {
             VocabQueryField::Vid,
         ],
     );
-    assert!(resp.is_ok());
+    let entries = resp.unwrap();
+    assert_eq!(entries[&(Vid(0), Sid(0))], None);
+    let entry = entries[&(Vid(12), Sid(12))].as_ref().unwrap();
+    assert_eq!(entry.spelling.as_deref(), Some("死神"));
+    assert_eq!(entry.card_state, Some(vec![CardState::New]));
 }
 
 #[test]
 fn mock_parse_text() {
-    let client = Client::new_mock("aaa", None);
+    let (_, client) = fake();
     let resp = client.parse_text(
-        "scouchou",
+        "死神の",
         &[
             TokenQueryField::Furigana,
             TokenQueryField::LengthUtf32,
@@ -240,6 +383,18 @@ fn mock_parse_text() {
             VocabQueryField::Vid,
         ]),
     );
-    dbg!(&resp);
-    assert!(resp.is_ok());
+    let result = resp.unwrap();
+    assert_eq!(result.tokens.len(), 2);
+    assert_eq!(result.tokens[1].position_utf8, Some(6));
+    assert_eq!(result.tokens[1].position_utf32, Some(2));
+    assert_eq!(result.vocabulary.len(), 2);
+}
+
+#[test]
+fn mock_too_many_requests() {
+    let (fake, client) = fake();
+    fake.fail_next(429, "too_many_requests", "slow down");
+    let resp = client.ping();
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::TooManyRequests);
+    assert!(client.ping().is_ok());
 }
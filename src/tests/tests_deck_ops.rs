@@ -0,0 +1,203 @@
+use serde_json::json;
+
+use super::fake_with_decks;
+use crate::{
+    client::Client,
+    deck_ops::{DeckTarget, SetOperation},
+    error::Error,
+    request::{AnyDeckId, AnyDeckWidget, UserDeckId, Vocabulary},
+    testing::FakeJpdb,
+};
+
+/// A fake knowing words 1 to 5, with an anime deck holding words 1 to 3 and a core deck holding
+/// words 2 to 4
+fn fake() -> (FakeJpdb, Client, UserDeckId, UserDeckId) {
+    let (fake, [anime, core]) = fake_with_decks([
+        (
+            "anime",
+            &[
+                (Vocabulary(1, 1), 1),
+                (Vocabulary(2, 2), 2),
+                (Vocabulary(3, 3), 3),
+            ],
+        ),
+        (
+            "core",
+            &[
+                (Vocabulary(4, 4), 1),
+                (Vocabulary(3, 3), 1),
+                (Vocabulary(2, 2), 1),
+            ],
+        ),
+    ]);
+    let client = fake.client();
+    (fake, client, anime, core)
+}
+
+fn combine(operation: SetOperation) -> Vec<(Vocabulary, u32)> {
+    let (fake, client, anime, core) = fake();
+    let id = client
+        .combine_decks(
+            operation,
+            &[anime.as_any(), core.as_any()],
+            DeckTarget::New {
+                name: "result",
+                position: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(fake.deck_names(), ["anime", "core", "result"]);
+    fake.deck_vocabulary(id).unwrap()
+}
+
+#[test]
+fn set_operations() {
+    assert_eq!(
+        combine(SetOperation::Union),
+        [
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(2, 2), 3),
+            (Vocabulary(3, 3), 4),
+            (Vocabulary(4, 4), 1),
+        ]
+    );
+    assert_eq!(
+        combine(SetOperation::Intersection),
+        [(Vocabulary(2, 2), 3), (Vocabulary(3, 3), 4)]
+    );
+    assert_eq!(combine(SetOperation::Difference), [(Vocabulary(1, 1), 1)]);
+}
+
+#[test]
+fn combine_into_existing_deck() {
+    let (fake, client, anime, core) = fake();
+    let id = client
+        .combine_decks(
+            SetOperation::Difference,
+            &[core.as_any(), anime.as_any()],
+            DeckTarget::Existing(anime.as_any()),
+        )
+        .unwrap();
+    assert_eq!(id, AnyDeckWidget::UserDeckId(anime.0));
+    assert_eq!(
+        fake.deck_vocabulary(anime).unwrap(),
+        [
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(2, 2), 2),
+            (Vocabulary(3, 3), 3),
+            (Vocabulary(4, 4), 1),
+        ]
+    );
+    assert!(matches!(
+        client.combine_decks(SetOperation::Union, &[], DeckTarget::Existing(id)),
+        Err(Error::InvalidRequest("decks", _))
+    ));
+}
+
+#[test]
+fn combine_into_source_deck() {
+    let combine_into_anime = |operation| {
+        let (fake, client, anime, core) = fake();
+        client
+            .combine_decks(
+                operation,
+                &[anime.as_any(), core.as_any()],
+                DeckTarget::Existing(anime.as_any()),
+            )
+            .unwrap();
+        fake.deck_vocabulary(anime).unwrap()
+    };
+    // The occurences of anime aren't counted twice
+    assert_eq!(
+        combine_into_anime(SetOperation::Union),
+        [
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(2, 2), 3),
+            (Vocabulary(3, 3), 4),
+            (Vocabulary(4, 4), 1),
+        ]
+    );
+    // Word 1 isn't part of the intersection, but was already in anime
+    assert_eq!(
+        combine_into_anime(SetOperation::Intersection),
+        [
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(2, 2), 3),
+            (Vocabulary(3, 3), 4),
+        ]
+    );
+}
+
+#[test]
+fn move_vocabulary() {
+    let (fake, client, anime, core) = fake();
+    client
+        .move_vocabulary(anime, core, &[Vocabulary(3, 3), Vocabulary(1, 1)])
+        .unwrap();
+    assert_eq!(
+        fake.deck_vocabulary(anime).unwrap(),
+        [(Vocabulary(2, 2), 2)]
+    );
+    assert_eq!(
+        fake.deck_vocabulary(core).unwrap(),
+        [
+            (Vocabulary(4, 4), 1),
+            (Vocabulary(3, 3), 4),
+            (Vocabulary(2, 2), 1),
+            (Vocabulary(1, 1), 1),
+        ]
+    );
+
+    let requests = fake.requests().len();
+    assert!(matches!(
+        client.move_vocabulary(anime, core, &[Vocabulary(5, 5)]),
+        Err(Error::InvalidRequest("vocabulary", _))
+    ));
+    assert!(matches!(
+        client.move_vocabulary(anime, anime, &[Vocabulary(2, 2)]),
+        Err(Error::InvalidRequest("to", _))
+    ));
+    // Only the source deck was listed
+    assert_eq!(fake.requests().len(), requests + 1);
+}
+
+#[test]
+fn move_vocabulary_overwrites_totals() {
+    let (fake, client, anime, core) = fake();
+    client
+        .move_vocabulary(anime, core, &[Vocabulary(3, 3)])
+        .unwrap();
+    // Sending the totals again, eg. when retrying, doesn't change them
+    let add = fake
+        .requests()
+        .into_iter()
+        .find(|request| request.url.ends_with("deck/add-vocabulary"))
+        .unwrap();
+    assert_eq!(add.body["occurences"], json!([4]));
+    assert_eq!(add.body["replace_existing_occurences"], json!(true));
+    client.call_raw("deck/add-vocabulary", &add.body).unwrap();
+    assert_eq!(
+        fake.deck_vocabulary(core).unwrap(),
+        [
+            (Vocabulary(4, 4), 1),
+            (Vocabulary(3, 3), 4),
+            (Vocabulary(2, 2), 1),
+        ]
+    );
+}
+
+#[test]
+fn merge_decks() {
+    let (fake, client, anime, core) = fake();
+    client.merge_decks(anime, core).unwrap();
+    assert_eq!(fake.deck_vocabulary(anime).unwrap(), []);
+    assert_eq!(
+        fake.deck_vocabulary(core).unwrap(),
+        [
+            (Vocabulary(4, 4), 1),
+            (Vocabulary(3, 3), 4),
+            (Vocabulary(2, 2), 3),
+            (Vocabulary(1, 1), 1),
+        ]
+    );
+}
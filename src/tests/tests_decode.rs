@@ -0,0 +1,256 @@
+use serde_json::json;
+
+use crate::{
+    card::CardState,
+    error::{Error, ErrorKind},
+    request::{
+        decode_rows, dedup_fields, AnyDeckWidget, Deck, DeckQueryField, Furigana,
+        ListDecksResponse, LookupVocabularyResponse, Meaning, ParseResponse, ParseResult, Sid,
+        Token, TokenQueryField, Vid, VocabQueryField, VocabularyEntry,
+    },
+};
+
+#[test]
+fn decode_parse_response() {
+    let response: ParseResponse = serde_json::from_value(json!({
+        "tokens": [[0, 0, [["漢字", "かんじ"], "を"]], [1, 9, null]],
+        "vocabulary": [[1310890, 1197989957, "漢字", ["kanji"], null]],
+    }))
+    .unwrap();
+    let result = ParseResult::from_response(
+        &[
+            TokenQueryField::VocabIndex,
+            TokenQueryField::PositionUtf8,
+            TokenQueryField::Furigana,
+        ],
+        &[
+            VocabQueryField::Vid,
+            VocabQueryField::Sid,
+            VocabQueryField::Spelling,
+            VocabQueryField::Meanings,
+            VocabQueryField::CardState,
+        ],
+        response,
+    )
+    .unwrap();
+
+    assert_eq!(
+        result.tokens,
+        vec![
+            Token {
+                vocabulary_index: Some(0),
+                position_utf8: Some(0),
+                furigana: Some(vec![
+                    Furigana::Ruby(String::from("漢字"), String::from("かんじ")),
+                    Furigana::Plain(String::from("を")),
+                ]),
+                ..Default::default()
+            },
+            Token {
+                vocabulary_index: Some(1),
+                position_utf8: Some(9),
+                ..Default::default()
+            },
+        ]
+    );
+    assert_eq!(
+        result.vocabulary,
+        vec![VocabularyEntry {
+            vid: Some(Vid(1310890)),
+            sid: Some(Sid(1197989957)),
+            spelling: Some(String::from("漢字")),
+            meanings: Some(vec![String::from("kanji")]),
+            ..Default::default()
+        }]
+    );
+}
+
+#[test]
+fn decode_parse_response_bad_row() {
+    let response: ParseResponse = serde_json::from_value(json!({
+        "tokens": [[0, 0]],
+        "vocabulary": [],
+    }))
+    .unwrap();
+    let result = ParseResult::from_response(&[TokenQueryField::VocabIndex], &[], response);
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::UnexpectedResponse);
+}
+
+#[test]
+fn decode_lookup_vocabulary() {
+    let response: LookupVocabularyResponse = serde_json::from_value(json!({
+        "vocabulary_info": [["かんじ", 2000, 1, ["learning"]], null],
+    }))
+    .unwrap();
+    let list = [(Vid(1310890), Sid(1197989957)), (Vid(1), Sid(1))];
+    let entries = response
+        .into_entries(
+            &list,
+            &[
+                VocabQueryField::Reading,
+                VocabQueryField::FrequencyRank,
+                VocabQueryField::CardLevel,
+                VocabQueryField::CardState,
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[&list[1]], None);
+    assert_eq!(
+        entries[&list[0]],
+        Some(VocabularyEntry {
+            reading: Some(String::from("かんじ")),
+            frequency_rank: Some(2000),
+            card_level: Some(1),
+            card_state: Some(vec![CardState::Learning]),
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+fn decode_list_decks() {
+    let response: ListDecksResponse = serde_json::from_value(json!({
+        "decks": [[3, "Core", 12.5], ["never-forget", "Never forget", 0]],
+    }))
+    .unwrap();
+    let decks = response
+        .into_decks(&[
+            DeckQueryField::Id,
+            DeckQueryField::Name,
+            DeckQueryField::KnownCoverage,
+        ])
+        .unwrap();
+
+    assert_eq!(
+        decks,
+        vec![
+            Deck {
+                id: Some(AnyDeckWidget::UserDeckId(3)),
+                name: Some(String::from("Core")),
+                known_coverage: Some(12.5),
+                ..Default::default()
+            },
+            Deck {
+                id: Some(AnyDeckWidget::NeverForget),
+                name: Some(String::from("Never forget")),
+                known_coverage: Some(0.0),
+                ..Default::default()
+            },
+        ]
+    );
+}
+
+#[test]
+fn decode_list_decks_unknown_special_deck() {
+    let response: ListDecksResponse = serde_json::from_value(json!({
+        "decks": [["whitelist"]],
+    }))
+    .unwrap();
+    let decks = response.into_decks(&[DeckQueryField::Id]).unwrap();
+    let id = decks[0].id.clone().unwrap();
+    assert_eq!(id, AnyDeckWidget::Unknown(String::from("whitelist")));
+    // Sent back as jpdb listed it
+    assert_eq!(serde_json::to_value(&id).unwrap(), json!("whitelist"));
+}
+
+#[test]
+fn decode_rows_wrong_type() {
+    let rows = vec![vec![json!(1)], vec![json!("one")]];
+    let result = decode_rows::<Token>(&[TokenQueryField::VocabIndex], rows);
+    let Err(Error::UnexpectedResponse(message)) = result else {
+        panic!("expected an unexpected response error, got {result:?}");
+    };
+    assert!(message.starts_with("row 1, field \"vocabulary_index\""));
+}
+
+#[test]
+fn dedup_fields_keeps_order() {
+    let fields = dedup_fields(&[
+        DeckQueryField::Name,
+        DeckQueryField::Id,
+        DeckQueryField::Name,
+        DeckQueryField::WordCount,
+        DeckQueryField::Id,
+    ]);
+    assert_eq!(
+        fields,
+        vec![
+            DeckQueryField::Name,
+            DeckQueryField::Id,
+            DeckQueryField::WordCount
+        ]
+    );
+}
+
+#[test]
+fn decode_extended_vocabulary_fields() {
+    let fields = [
+        VocabQueryField::MeaningsPartOfSpeech,
+        VocabQueryField::PartOfSpeech,
+        VocabQueryField::MeaningsChunks,
+        VocabQueryField::AltSids,
+        VocabQueryField::AltSpellings,
+        VocabQueryField::PitchAccent,
+        VocabQueryField::Other(String::from("spelling_romaji")),
+    ];
+    assert_eq!(
+        serde_json::to_value(&fields).unwrap(),
+        json!([
+            "meanings_part_of_speech",
+            "part_of_speech",
+            "meanings_chunks",
+            "alt_sids",
+            "alt_spellings",
+            "pitch_accent",
+            "spelling_romaji",
+        ])
+    );
+    let entries: Vec<VocabularyEntry> = decode_rows(
+        &fields,
+        vec![vec![
+            json!([["n"], ["v1", "vt"]]),
+            json!(["n", "v1", "vt"]),
+            json!([["god of death"], ["to eat", "to live on"]]),
+            json!([42]),
+            json!(["しにがみ"]),
+            json!(["LHHH"]),
+            json!("shinigami"),
+        ]],
+    )
+    .unwrap();
+
+    let entry = &entries[0];
+    assert_eq!(
+        entry.meaning_chunks,
+        Some(vec![
+            Meaning {
+                glosses: vec![String::from("god of death")],
+                part_of_speech: vec![String::from("n")],
+            },
+            Meaning {
+                glosses: vec![String::from("to eat"), String::from("to live on")],
+                part_of_speech: vec![String::from("v1"), String::from("vt")],
+            },
+        ])
+    );
+    assert_eq!(entry.part_of_speech.as_ref().unwrap().len(), 3);
+    assert_eq!(entry.alt_sids, Some(vec![Sid(42)]));
+    assert_eq!(entry.alt_spellings, Some(vec![String::from("しにがみ")]));
+    assert_eq!(entry.pitch_accent, Some(vec![String::from("LHHH")]));
+    assert_eq!(entry.other["spelling_romaji"], json!("shinigami"));
+}
+
+#[test]
+fn decode_known_fields_given_as_other() {
+    assert_eq!(VocabQueryField::from_name("vid"), VocabQueryField::Vid);
+    assert_eq!(
+        VocabQueryField::from_name("spelling_romaji"),
+        VocabQueryField::Other(String::from("spelling_romaji"))
+    );
+    let fields = [VocabQueryField::Other(String::from("vid"))];
+    let entries: Vec<VocabularyEntry> = decode_rows(&fields, vec![vec![json!(12)]]).unwrap();
+    assert_eq!(entries[0].vid, Some(Vid(12)));
+    assert!(entries[0].other.is_empty());
+}
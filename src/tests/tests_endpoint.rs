@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    endpoint::{AddVocabulary, CreateEmptyDeck, Endpoint, ListVocabulary, Ping},
+    error::{Error, ErrorKind},
+    request::{
+        AddVocabularyOptions, AnyDeckWidget, Request, SetCardSentenceOptions, Sid, Vid, Vocabulary,
+    },
+    testing::{FakeJpdb, FakeWord},
+};
+
+#[test]
+fn request_new_appends_path() {
+    let request = Request::new("https://jpdb.io/api/v1/", &Ping).unwrap();
+    assert_eq!(request.url, "https://jpdb.io/api/v1/ping");
+    assert_eq!(request.body, serde_json::Value::Null);
+}
+
+#[test]
+fn request_new_reports_serialization_errors() {
+    // Maps can only be serialized to JSON with string keys
+    #[derive(Serialize)]
+    struct Tagged {
+        tags: std::collections::HashMap<(u32, u32), u32>,
+    }
+
+    impl Endpoint for Tagged {
+        const PATH: &'static str = "tag";
+        type Response = serde::de::IgnoredAny;
+    }
+
+    let endpoint = Tagged {
+        tags: [((1, 1), 2)].into(),
+    };
+    let error = Request::new("", &endpoint).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::SerializeError);
+}
+
+#[test]
+fn create_empty_deck_skips_missing_position() {
+    let request = Request::new(
+        "",
+        &CreateEmptyDeck {
+            name: "deck",
+            position: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(request.body, json!({ "name": "deck" }));
+}
+
+#[test]
+fn add_vocabulary_flattens_options() {
+    let endpoint = AddVocabulary {
+        id: AnyDeckWidget::NeverForget,
+        options: AddVocabularyOptions {
+            vocabulary: &[Vocabulary(1, 2)],
+            occurences: Some(&[3]),
+            overwrite_occurences: None,
+            ignore_unknown: Some(true),
+        },
+    };
+    let request = Request::new("", &endpoint).unwrap();
+    assert_eq!(
+        request.body,
+        json!({
+            "id": "never-forget",
+            "vocabulary": [[1, 2]],
+            "occurences": [3],
+            "ignore_unknown": true,
+        })
+    );
+}
+
+#[test]
+fn execute_crate_endpoint() {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    let deck = fake.add_deck("deck", &[(Vocabulary(12, 12), 4)]);
+    let response = fake
+        .client()
+        .execute(&ListVocabulary {
+            id: AnyDeckWidget::UserDeckId(deck.0),
+            fetch_occurences: Some(true),
+        })
+        .unwrap();
+    assert_eq!(response.vocabulary, vec![Vocabulary(12, 12)]);
+    assert_eq!(response.occurences, Some(vec![4]));
+}
+
+/// An endpoint defined the way a downstream crate would
+#[derive(Serialize)]
+struct Rename<'a> {
+    id: u8,
+    name: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct Renamed {}
+
+impl Endpoint for Rename<'_> {
+    const PATH: &'static str = "deck/rename";
+    type Response = Renamed;
+}
+
+#[test]
+fn execute_downstream_endpoint() {
+    let fake = FakeJpdb::new("aaa");
+    let deck = fake.add_deck("deck", &[]);
+    let client = fake.client();
+    client
+        .execute(&Rename {
+            id: deck.0,
+            name: "renamed",
+        })
+        .unwrap();
+    assert_eq!(fake.deck_names(), vec![String::from("renamed")]);
+
+    let error = client
+        .execute(&Rename {
+            id: 42,
+            name: "nope",
+        })
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::BadDeck);
+}
+
+#[test]
+fn call_raw_returns_json() {
+    let fake = FakeJpdb::new("aaa");
+    let deck = fake.add_deck("deck", &[]);
+    let client = fake.client();
+    let response = client
+        .call_raw("/deck/create-empty", &json!({ "name": "other" }))
+        .unwrap();
+    assert_eq!(response, json!({ "id": deck.0 + 1 }));
+    assert!(fake.requests()[0]
+        .url
+        .ends_with("/api/v1/deck/create-empty"));
+}
+
+#[test]
+fn call_raw_maps_errors() {
+    let fake = FakeJpdb::new("aaa");
+    let error = fake
+        .client()
+        .call_raw("deck/rename", &json!({ "id": 42, "name": "nope" }))
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::BadDeck);
+}
+
+fn invalid_field<T: std::fmt::Debug>(result: Result<T, Error>) -> &'static str {
+    match result {
+        Err(Error::InvalidRequest(field, _)) => field,
+        other => panic!("expected an invalid request, got {other:?}"),
+    }
+}
+
+#[test]
+fn set_card_sentence_rejects_default_ids() {
+    let fake = FakeJpdb::new("aaa");
+    let client = fake.client();
+    let options = SetCardSentenceOptions::default();
+    assert_eq!(invalid_field(client.set_card_sentence(&options)), "vid");
+    let options = SetCardSentenceOptions {
+        vid: Vid(12),
+        ..Default::default()
+    };
+    assert_eq!(invalid_field(client.set_card_sentence(&options)), "sid");
+    assert!(fake.requests().is_empty());
+}
+
+#[test]
+fn set_card_sentence_leaves_text_to_jpdb() {
+    // What jpdb accepts isn't documented, an empty sentence is sent as is
+    let options = SetCardSentenceOptions {
+        vid: Vid(12),
+        sid: Sid(12),
+        sentence: Some(""),
+        translation: Some(&"a".repeat(5000)),
+        ..Default::default()
+    };
+    assert!(options.validate().is_ok());
+}
+
+#[test]
+fn set_card_sentence_validates_spelling() {
+    let options = SetCardSentenceOptions {
+        vid: Vid(12),
+        sid: Sid(12),
+        sentence: Some("死神だ"),
+        ..Default::default()
+    };
+    assert!(options.validate().is_ok());
+    assert!(options.validate_spelling("死神").is_ok());
+    assert_eq!(invalid_field(options.validate_spelling("の")), "sentence");
+}
@@ -0,0 +1,59 @@
+use crate::error::{Error, ErrorKind};
+
+fn kind(code: u16, body: &str) -> ErrorKind {
+    Error::from_response(code, body.as_bytes()).kind()
+}
+
+fn jpdb_error(error: &str) -> String {
+    format!(r#"{{"error": "{error}", "error_message": "message"}}"#)
+}
+
+#[test]
+fn error_documented_codes() {
+    let documented = [
+        (403, "bad_key", ErrorKind::BadKey),
+        (429, "too_many_requests", ErrorKind::TooManyRequests),
+        (503, "api_unavailable", ErrorKind::ApiUnavailable),
+        (400, "bad_request", ErrorKind::BadRequest),
+        (400, "too_many_decks", ErrorKind::TooManyDecks),
+        (400, "too_many_cards_in_deck", ErrorKind::TooManyCardsInDeck),
+        (400, "too_many_cards_total", ErrorKind::TooManyCardsTotal),
+        (400, "bad_deck", ErrorKind::BadDeck),
+        (400, "bad_vid", ErrorKind::BadVid),
+        (400, "bad_sid", ErrorKind::BadSid),
+        (400, "bad_rid", ErrorKind::BadRid),
+        (400, "bad_image", ErrorKind::BadImage),
+        (400, "bad_audio", ErrorKind::BadAudio),
+        (400, "bad_sentence", ErrorKind::BadSentence),
+        (400, "bad_translation", ErrorKind::BadTranslation),
+    ];
+    for (code, error, expected) in documented {
+        assert_eq!(kind(code, &jpdb_error(error)), expected, "{error}");
+    }
+}
+
+#[test]
+fn error_missing_key() {
+    let body = r#"{"error": "bad_key", "error_message": "missing API key"}"#;
+    assert_eq!(kind(403, body), ErrorKind::MissingKey);
+}
+
+#[test]
+fn error_non_json_bodies() {
+    assert_eq!(
+        kind(503, "<html>Service Unavailable</html>"),
+        ErrorKind::ApiUnavailable
+    );
+    assert_eq!(kind(502, ""), ErrorKind::ApiUnavailable);
+    assert_eq!(kind(429, "slow down"), ErrorKind::TooManyRequests);
+
+    let Error::Unhandled(404, raw) = Error::from_response(404, b"Not Found") else {
+        panic!("expected an unhandled error");
+    };
+    assert_eq!(raw.error_message, "Not Found");
+}
+
+#[test]
+fn error_unknown_code() {
+    assert_eq!(kind(400, &jpdb_error("bad_mood")), ErrorKind::Unhandled);
+}
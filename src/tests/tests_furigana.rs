@@ -0,0 +1,111 @@
+use crate::{
+    card::CardState,
+    error::Error,
+    furigana::{render_anki, render_html, render_reading, FuriganaOptions},
+    request::{
+        Furigana, ParseResult, Token, TokenQueryField, VocabQueryField, Vocabulary, VocabularyEntry,
+    },
+    testing::{FakeJpdb, FakeWord},
+};
+
+fn token(index: usize, position: usize, length: usize, furigana: Option<Vec<Furigana>>) -> Token {
+    Token {
+        vocabulary_index: Some(index),
+        position_utf8: Some(position),
+        length_utf8: Some(length),
+        furigana,
+        ..Default::default()
+    }
+}
+
+fn ruby(base: &str, reading: &str) -> Furigana {
+    Furigana::Ruby(base.to_owned(), reading.to_owned())
+}
+
+fn plain(text: &str) -> Furigana {
+    Furigana::Plain(text.to_owned())
+}
+
+fn entry(state: &str) -> VocabularyEntry {
+    VocabularyEntry {
+        card_state: Some(vec![CardState::from(state)]),
+        ..Default::default()
+    }
+}
+
+/// 「食べる<本>」, where 食べる is known and 本 isn't
+fn parsed() -> (&'static str, ParseResult) {
+    let text = "「食べる<本>」";
+    let result = ParseResult {
+        tokens: vec![
+            token(0, 3, 9, Some(vec![ruby("食", "た"), plain("べる")])),
+            token(1, 13, 3, Some(vec![ruby("本", "ほん")])),
+        ],
+        vocabulary: vec![entry("known"), entry("learning")],
+    };
+    (text, result)
+}
+
+#[test]
+fn html() {
+    let (text, parsed) = parsed();
+    let html = render_html(text, &parsed, FuriganaOptions::default()).unwrap();
+    assert_eq!(
+        html,
+        "「<ruby>食<rt>た</rt></ruby>べる&lt;<ruby>本<rt>ほん</rt></ruby>&gt;」"
+    );
+}
+
+#[test]
+fn anki() {
+    let (text, parsed) = parsed();
+    let anki = render_anki(text, &parsed, FuriganaOptions::default()).unwrap();
+    assert_eq!(anki, "「 食[た]べる< 本[ほん]>」");
+}
+
+#[test]
+fn reading() {
+    let (text, parsed) = parsed();
+    let reading = render_reading(text, &parsed, FuriganaOptions::default()).unwrap();
+    assert_eq!(reading, "「たべる<ほん>」");
+}
+
+#[test]
+fn skip_known() {
+    let (text, parsed) = parsed();
+    let options = FuriganaOptions { skip_known: true };
+    assert_eq!(
+        render_anki(text, &parsed, options).unwrap(),
+        "「食べる< 本[ほん]>」"
+    );
+}
+
+#[test]
+fn missing_positions() {
+    let (text, mut parsed) = parsed();
+    parsed.tokens[1].length_utf8 = None;
+    let error = render_html(text, &parsed, FuriganaOptions::default()).unwrap_err();
+    assert!(matches!(error, Error::MissingField("length_utf8", _)));
+}
+
+#[test]
+fn render_parsed_text() {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    fake.add_word(FakeWord::new(Vocabulary(13, 13), "の", "の"));
+    let text = "死神の死神";
+    let parsed = fake
+        .client()
+        .parse_text(
+            text,
+            &[
+                TokenQueryField::PositionUtf8,
+                TokenQueryField::LengthUtf8,
+                TokenQueryField::Furigana,
+            ],
+            Some(&[VocabQueryField::CardState]),
+        )
+        .unwrap();
+    let anki = render_anki(text, &parsed, FuriganaOptions::default()).unwrap();
+    assert_eq!(anki, "死神[しにがみ]の 死神[しにがみ]");
+}
@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    client::Client,
+    error::ErrorKind,
+    governor::{RateLimit, RetryPolicy},
+    request::{AddVocabularyOptions, Vocabulary},
+    testing::{FakeJpdb, FakeWord},
+    transport::HttpResponse,
+};
+
+fn quick_retry(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn retry_too_many_requests() {
+    let fake = FakeJpdb::new("aaa");
+    let client = Client::builder("aaa")
+        .transport(fake.clone())
+        .retry(quick_retry(3))
+        .build()
+        .unwrap();
+    fake.fail_next(429, "too_many_requests", "slow down");
+    fake.fail_next(429, "too_many_requests", "slow down");
+    assert!(client.ping().is_ok());
+    assert_eq!(fake.requests().len(), 3);
+}
+
+#[test]
+fn retry_gives_up() {
+    let fake = FakeJpdb::new("aaa");
+    let client = Client::builder("aaa")
+        .transport(fake.clone())
+        .retry(quick_retry(1))
+        .build()
+        .unwrap();
+    fake.fail_next(429, "too_many_requests", "slow down");
+    fake.fail_next(429, "too_many_requests", "slow down");
+    assert_eq!(
+        client.ping().unwrap_err().kind(),
+        ErrorKind::TooManyRequests
+    );
+    assert_eq!(fake.requests().len(), 2);
+}
+
+#[test]
+fn retry_ignores_other_errors() {
+    let fake = FakeJpdb::new("aaa");
+    let client = Client::builder("bbb")
+        .transport(fake.clone())
+        .retry(quick_retry(3))
+        .build()
+        .unwrap();
+    assert_eq!(client.ping().unwrap_err().kind(), ErrorKind::BadKey);
+    assert_eq!(fake.requests().len(), 1);
+}
+
+#[test]
+fn retry_only_idempotent_when_unavailable() {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    let deck = fake.add_deck("deck", &[]);
+    let client = Client::builder("aaa")
+        .transport(fake.clone())
+        .retry(quick_retry(3))
+        .build()
+        .unwrap();
+    let options = AddVocabularyOptions {
+        vocabulary: &[Vocabulary(12, 12)],
+        occurences: Some(&[2]),
+        ..Default::default()
+    };
+    // The first request may have been applied, sending it again could count the words twice
+    fake.fail_next(503, "api_unavailable", "down for maintenance");
+    assert_eq!(
+        client.add_vocabulary(deck, &options).unwrap_err().kind(),
+        ErrorKind::ApiUnavailable
+    );
+    assert_eq!(fake.requests().len(), 1);
+    // It wasn't when jpdb asks to slow down
+    fake.fail_next(429, "too_many_requests", "slow down");
+    client.add_vocabulary(deck, &options).unwrap();
+    assert_eq!(fake.requests().len(), 3);
+    // Overwriting the occurences is safe to repeat
+    fake.fail_next(503, "api_unavailable", "down for maintenance");
+    client
+        .add_vocabulary(
+            deck,
+            &AddVocabularyOptions {
+                overwrite_occurences: Some(true),
+                ..options
+            },
+        )
+        .unwrap();
+    assert_eq!(fake.requests().len(), 5);
+    assert_eq!(
+        fake.deck_vocabulary(deck),
+        Some(vec![(Vocabulary(12, 12), 2)])
+    );
+}
+
+#[test]
+fn retry_backoff() {
+    let policy = RetryPolicy {
+        max_retries: 10,
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(5),
+        multiplier: 2.0,
+    };
+    assert_eq!(policy.backoff(0), Duration::from_secs(1));
+    assert_eq!(policy.backoff(2), Duration::from_secs(4));
+    assert_eq!(policy.backoff(3), Duration::from_secs(5));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(5));
+    assert_eq!(
+        policy.delay(0, Some(Duration::from_secs(3))),
+        Duration::from_secs(3)
+    );
+    assert_eq!(
+        policy.delay(0, Some(Duration::from_secs(600))),
+        Duration::from_secs(5)
+    );
+}
+
+#[test]
+fn retry_after_header() {
+    let response = HttpResponse::new(429, Vec::new()).with_header("retry-after", " 3 ");
+    assert_eq!(response.retry_after(), Some(Duration::from_secs(3)));
+    let response = HttpResponse::new(429, Vec::new())
+        .with_header("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT");
+    assert_eq!(response.retry_after(), None);
+}
+
+#[test]
+fn rate_limit_waits() {
+    let fake = FakeJpdb::new("aaa");
+    let client = Client::builder("aaa")
+        .transport(fake)
+        .rate_limit(RateLimit::new(2, Duration::from_millis(100)))
+        .build()
+        .unwrap();
+    let start = Instant::now();
+    for _ in 0..4 {
+        client.ping().unwrap();
+    }
+    // The first two go through right away, the two others wait for a refill
+    assert!(start.elapsed() >= Duration::from_millis(90));
+}
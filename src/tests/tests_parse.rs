@@ -0,0 +1,155 @@
+use crate::{
+    parse::{pack_texts, split_text},
+    request::{TokenQueryField, VocabQueryField, Vocabulary},
+    testing::{FakeJpdb, FakeWord},
+};
+
+fn pieces(text: &str, budget: usize) -> Vec<&str> {
+    split_text(text, budget)
+        .into_iter()
+        .map(|piece| &text[piece.range])
+        .collect()
+}
+
+#[test]
+fn split_short_text() {
+    assert_eq!(pieces("死神の", 100), vec!["死神の"]);
+    assert_eq!(pieces("", 100), vec![""]);
+}
+
+#[test]
+fn split_prefers_paragraphs() {
+    // Every character is 3 bytes long
+    let text = "あい。うえ。\nおか。きく";
+    assert_eq!(pieces(text, 24), vec!["あい。うえ。\n", "おか。きく"]);
+}
+
+#[test]
+fn split_on_sentences() {
+    let text = "「あい。」うえ。おか";
+    assert_eq!(pieces(text, 21), vec!["「あい。」", "うえ。おか"]);
+}
+
+#[test]
+fn split_anywhere_without_boundaries() {
+    let text = "あいうえお";
+    assert_eq!(pieces(text, 7), vec!["あい", "うえ", "お"]);
+    // A budget smaller than a character still makes progress
+    assert_eq!(pieces("あい", 1), vec!["あ", "い"]);
+}
+
+#[test]
+fn split_positions() {
+    let text = "ab。\ncdあ。え";
+    let pieces = split_text(text, 8);
+    let starts: Vec<_> = pieces
+        .iter()
+        .map(|p| (p.range.start, p.start_utf32))
+        .collect();
+    assert_eq!(starts, vec![(0, 0), (6, 4), (14, 8)]);
+}
+
+#[test]
+fn parse_long_text_matches_single_parse() {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    fake.add_word(FakeWord::new(Vocabulary(13, 13), "の", "の"));
+    let client = fake.client();
+    let text = "死神の。\n死神だ。のの死神";
+    let token_fields = [
+        TokenQueryField::VocabIndex,
+        TokenQueryField::PositionUtf8,
+        TokenQueryField::PositionUtf32,
+        TokenQueryField::LengthUtf8,
+    ];
+    let vocab_fields = [
+        VocabQueryField::Vid,
+        VocabQueryField::Sid,
+        VocabQueryField::Spelling,
+    ];
+
+    let whole = client
+        .parse_text(text, &token_fields, Some(&vocab_fields))
+        .unwrap();
+    let long = client
+        .parse_long_text(text, &token_fields, Some(&vocab_fields), 12)
+        .unwrap();
+    assert!(fake.requests().len() > 2);
+    assert_eq!(long, whole);
+}
+
+#[test]
+fn parse_long_text_forces_merge_fields() {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    let client = fake.client();
+    let result = client
+        .parse_long_text("死神。死神。", &[TokenQueryField::PositionUtf32], None, 9)
+        .unwrap();
+    assert_eq!(result.vocabulary.len(), 1);
+    let positions: Vec<_> = result
+        .tokens
+        .iter()
+        .map(|t| (t.vocabulary_index, t.position_utf32))
+        .collect();
+    assert_eq!(positions, vec![(Some(0), Some(0)), (Some(0), Some(3))]);
+}
+
+#[test]
+fn pack_texts_by_budget() {
+    assert_eq!(pack_texts(&["ab", "cd", "e", "fgh"], 5), [0..3, 3..4]);
+    // A text larger than the budget is sent on its own
+    assert_eq!(pack_texts(&["a", "bcdefg", "h"], 5), [0..1, 1..2, 2..3]);
+    assert_eq!(pack_texts(&["", "", "abcde", "f"], 5), [0..3, 3..4]);
+    assert!(pack_texts(&[], 5).is_empty());
+}
+
+#[test]
+fn parse_texts_shares_vocabulary() {
+    let fake = FakeJpdb::new("aaa");
+    fake.add_word(FakeWord::new(Vocabulary(12, 12), "死神", "しにがみ"));
+    fake.add_word(FakeWord::new(Vocabulary(13, 13), "の", "の"));
+    let texts = ["死神の", "", "の死神", "の"];
+    let result = fake
+        .client()
+        .parse_texts(
+            &texts,
+            &[TokenQueryField::PositionUtf8],
+            Some(&[VocabQueryField::Spelling]),
+            12,
+        )
+        .unwrap();
+
+    assert_eq!(fake.requests().len(), 2);
+    assert_eq!(result.vocabulary.len(), 2);
+    let spellings: Vec<Vec<_>> = result
+        .tokens
+        .iter()
+        .map(|tokens| {
+            tokens
+                .iter()
+                .map(|t| {
+                    let entry = &result.vocabulary[t.vocabulary_index.unwrap()];
+                    (entry.spelling.as_deref().unwrap(), t.position_utf8.unwrap())
+                })
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        spellings,
+        vec![
+            vec![("死神", 0), ("の", 6)],
+            vec![],
+            vec![("の", 0), ("死神", 3)],
+            vec![("の", 0)],
+        ]
+    );
+}
+
+#[test]
+fn parse_texts_without_texts() {
+    let fake = FakeJpdb::new("aaa");
+    let result = fake.client().parse_texts(&[], &[], None, 1000).unwrap();
+    assert!(result.tokens.is_empty());
+    assert!(fake.requests().is_empty());
+}
@@ -0,0 +1,109 @@
+use crate::{
+    error::Error,
+    pitch::{split_morae, Pitch, PitchAccent},
+    request::{Sid, Vid, VocabQueryField, Vocabulary},
+    testing::{FakeJpdb, FakeWord},
+};
+
+#[test]
+fn morae() {
+    assert_eq!(split_morae("きょうと"), ["きょ", "う", "と"]);
+    assert_eq!(split_morae("がっこう"), ["が", "っ", "こ", "う"]);
+    assert_eq!(split_morae("コーヒー"), ["コ", "ー", "ヒ", "ー"]);
+    assert_eq!(split_morae("ファン"), ["ファ", "ン"]);
+    assert!(split_morae("").is_empty());
+}
+
+#[test]
+fn downsteps() {
+    let downstep = |reading, pattern| PitchAccent::new(reading, pattern).unwrap().downstep();
+    assert_eq!(downstep("さくら", "LHHH"), 0);
+    assert_eq!(downstep("いのち", "HLLL"), 1);
+    assert_eq!(downstep("こころ", "LHLL"), 2);
+    assert_eq!(downstep("おとこ", "LHHL"), 3);
+    assert_eq!(downstep("おとこ", "LHH"), 0);
+    assert_eq!(downstep("きょう", "HL"), 1);
+}
+
+#[test]
+fn bad_patterns() {
+    assert!(matches!(
+        PitchAccent::new("さくら", "LHX"),
+        Err(Error::UnexpectedResponse(_))
+    ));
+    assert!(matches!(
+        PitchAccent::new("さくら", "LHHHH"),
+        Err(Error::UnexpectedResponse(_))
+    ));
+}
+
+#[test]
+fn html() {
+    let accent = PitchAccent::new("おとこ", "LHHL").unwrap();
+    assert_eq!(
+        accent.to_html(),
+        "<span class=\"pitch-low\">お</span><span class=\"pitch-high pitch-drop\">とこ</span>"
+    );
+    let accent = PitchAccent::new("きょうと", "HLLL").unwrap();
+    assert_eq!(
+        accent.to_html(),
+        "<span class=\"pitch-high pitch-drop\">きょ</span><span class=\"pitch-low\">うと</span>"
+    );
+    let accent = PitchAccent::new("さくら", "LHH").unwrap();
+    assert_eq!(
+        accent.to_html(),
+        "<span class=\"pitch-low\">さ</span><span class=\"pitch-high\">くら</span>"
+    );
+}
+
+#[test]
+fn svg() {
+    let svg = PitchAccent::new("おとこ", "LHHL").unwrap().to_svg();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+    assert!(svg.contains("points=\"12,28 36,8 60,8 84,28\""));
+    assert_eq!(svg.matches("<circle").count(), 4);
+    // The particle is hollow
+    assert_eq!(svg.matches("r=\"4\" fill=\"none\"").count(), 1);
+    assert_eq!(svg.matches("<text").count(), 3);
+}
+
+#[test]
+fn rendering_escapes_morae() {
+    let accent = PitchAccent::new("<&>", "LHH").unwrap();
+    assert_eq!(
+        accent.to_html(),
+        "<span class=\"pitch-low\">&lt;</span><span class=\"pitch-high\">&amp;&gt;</span>"
+    );
+    let svg = accent.to_svg();
+    assert!(svg.contains(">&lt;</text>") && svg.contains(">&amp;</text>"));
+    assert!(!svg.contains("<&"));
+}
+
+#[test]
+fn pitch_accents_of_entry() {
+    let fake = FakeJpdb::new("aaa");
+    let mut word = FakeWord::new(Vocabulary(1, 2), "男", "おとこ");
+    word.pitch_accent = vec![String::from("LHHL")];
+    fake.add_word(word);
+    let client = fake.client();
+    let list = [(Vid(1), Sid(2))];
+
+    let entries = client
+        .lookup_vocabulary(
+            &list,
+            &[VocabQueryField::Reading, VocabQueryField::PitchAccent],
+        )
+        .unwrap();
+    let accents = entries[&list[0]].as_ref().unwrap().pitch_accents().unwrap();
+    assert_eq!(accents.len(), 1);
+    assert_eq!(accents[0].morae, ["お", "と", "こ"]);
+    assert_eq!(accents[0].pitches[3], Pitch::Low);
+
+    let entries = client
+        .lookup_vocabulary(&list, &[VocabQueryField::PitchAccent])
+        .unwrap();
+    assert!(matches!(
+        entries[&list[0]].as_ref().unwrap().pitch_accents(),
+        Err(Error::MissingField("reading", _))
+    ));
+}
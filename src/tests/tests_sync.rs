@@ -0,0 +1,139 @@
+use super::fake_with_decks;
+use crate::{
+    client::Client,
+    request::{UserDeckId, Vocabulary},
+    sync::{OccurenceChange, SyncOptions, SyncReport},
+    testing::FakeJpdb,
+};
+
+/// A fake knowing words 1 to 5, with a deck holding words 1 to 3
+fn fake() -> (FakeJpdb, Client, UserDeckId) {
+    let (fake, [deck]) = fake_with_decks([(
+        "deck",
+        &[
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(2, 2), 2),
+            (Vocabulary(3, 3), 3),
+        ],
+    )]);
+    let client = fake.client();
+    (fake, client, deck)
+}
+
+const DESIRED: [(Vocabulary, u16); 4] = [
+    (Vocabulary(4, 4), 1),
+    (Vocabulary(1, 1), 1),
+    (Vocabulary(3, 3), 5),
+    (Vocabulary(5, 5), 2),
+];
+
+#[test]
+fn sync_deck() {
+    let (fake, client, deck) = fake();
+    let report = client
+        .sync_deck(deck, &DESIRED, SyncOptions::default())
+        .unwrap();
+    assert_eq!(
+        report,
+        SyncReport {
+            added: vec![Vocabulary(4, 4), Vocabulary(5, 5)],
+            removed: vec![Vocabulary(2, 2)],
+            updated: vec![OccurenceChange {
+                vocabulary: Vocabulary(3, 3),
+                from: 3,
+                to: 5,
+            }],
+            unchanged: 1,
+            dry_run: false,
+        }
+    );
+    assert_eq!(
+        fake.deck_vocabulary(deck).unwrap(),
+        [
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(3, 3), 5),
+            (Vocabulary(4, 4), 1),
+            (Vocabulary(5, 5), 2),
+        ]
+    );
+    // Listing, removing, then adding and updating together
+    assert_eq!(fake.requests().len(), 3);
+
+    let report = client
+        .sync_deck(deck, &DESIRED, SyncOptions::default())
+        .unwrap();
+    assert!(report.is_empty());
+    assert_eq!(report.unchanged, 4);
+    assert_eq!(fake.requests().len(), 4);
+}
+
+#[test]
+fn sync_deck_dry_run() {
+    let (fake, client, deck) = fake();
+    let before = fake.deck_vocabulary(deck);
+    let report = client
+        .sync_deck(
+            deck,
+            &DESIRED,
+            SyncOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.added.len(), 2);
+    assert_eq!(report.removed, [Vocabulary(2, 2)]);
+    assert_eq!(fake.deck_vocabulary(deck), before);
+    assert_eq!(fake.requests().len(), 1);
+}
+
+#[test]
+fn sync_deck_ignoring_occurences() {
+    let (fake, client, deck) = fake();
+    let report = client
+        .sync_deck(
+            deck,
+            &DESIRED,
+            SyncOptions {
+                ignore_occurences: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(report.updated.is_empty());
+    assert_eq!(report.unchanged, 2);
+    assert_eq!(
+        fake.deck_vocabulary(deck).unwrap(),
+        [
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(3, 3), 3),
+            (Vocabulary(4, 4), 1),
+            (Vocabulary(5, 5), 1),
+        ]
+    );
+}
+
+#[test]
+fn sync_deck_merges_duplicates() {
+    let (fake, client, deck) = fake();
+    let desired = [
+        (Vocabulary(1, 1), 1),
+        (Vocabulary(2, 2), 2),
+        (Vocabulary(3, 3), 3),
+        (Vocabulary(2, 2), 2),
+    ];
+    let report = client
+        .sync_deck(deck, &desired, SyncOptions::default())
+        .unwrap();
+    assert_eq!(report.updated.len(), 1);
+    assert_eq!(report.updated[0].to, 4);
+    assert_eq!(
+        fake.deck_vocabulary(deck).unwrap(),
+        [
+            (Vocabulary(1, 1), 1),
+            (Vocabulary(2, 2), 4),
+            (Vocabulary(3, 3), 3),
+        ]
+    );
+}
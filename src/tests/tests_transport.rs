@@ -0,0 +1,113 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+
+use crate::{
+    client::Client,
+    error::ErrorKind,
+    request::{Request, UserDeckId},
+    testing::FakeJpdb,
+    transport::{HttpResponse, Transport, TransportError},
+};
+
+type Sent = Arc<Mutex<Vec<(Request, Vec<(String, String)>)>>>;
+
+/// Answers every request with the same response, and remembers what was sent
+#[derive(Debug)]
+struct CannedTransport {
+    response: HttpResponse,
+    sent: Sent,
+}
+
+impl Transport for CannedTransport {
+    fn send(
+        &self,
+        request: &Request,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, TransportError> {
+        let headers = headers
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect();
+        self.sent.lock().unwrap().push((request.clone(), headers));
+        Ok(self.response.clone())
+    }
+}
+
+fn canned_client(status: u16, body: serde_json::Value) -> (Client, Sent) {
+    let sent = Sent::default();
+    let transport = CannedTransport {
+        response: HttpResponse::new(status, body.to_string().into_bytes()),
+        sent: sent.clone(),
+    };
+    (Client::with_transport("aaa", transport), sent)
+}
+
+#[test]
+fn transport_receives_request() {
+    let (client, sent) = canned_client(200, json!({ "id": 4 }));
+    let resp = client.create_empty_deck("baba", None);
+    assert_eq!(resp.unwrap(), UserDeckId(4));
+
+    let sent = sent.lock().unwrap();
+    let (request, headers) = &sent[0];
+    assert_eq!(request.url, "https://jpdb.io/api/v1/deck/create-empty");
+    assert_eq!(request.body, json!({ "name": "baba" }));
+    assert!(headers.contains(&(String::from("Authorization"), String::from("Bearer aaa"))));
+}
+
+#[test]
+fn transport_error_status() {
+    let (client, _) = canned_client(
+        403,
+        json!({ "error": "bad_key", "error_message": "invalid API key" }),
+    );
+    let resp = client.ping();
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::BadKey);
+}
+
+#[test]
+fn transport_failure() {
+    #[derive(Debug)]
+    struct Unreachable;
+    impl Transport for Unreachable {
+        fn send(&self, _: &Request, _: &[(&str, &str)]) -> Result<HttpResponse, TransportError> {
+            Err("connection refused".into())
+        }
+    }
+
+    let client = Client::with_transport("aaa", Unreachable);
+    assert_eq!(client.ping().unwrap_err().kind(), ErrorKind::Transport);
+}
+
+#[test]
+fn http_response_header_case_insensitive() {
+    let response = HttpResponse::new(200, Vec::new()).with_header("Retry-After", "2");
+    assert_eq!(response.header("retry-after"), Some("2"));
+    assert_eq!(response.header("Content-Type"), None);
+}
+
+#[test]
+fn builder_base_url() {
+    let fake = FakeJpdb::new("aaa");
+    fake.set_base_url("http://localhost:8080/api/v1");
+    let client = Client::builder("aaa")
+        .base_url("http://localhost:8080/api/v1")
+        .transport(fake.clone())
+        .build()
+        .unwrap();
+    assert!(client.ping().is_ok());
+    assert_eq!(fake.requests()[0].url, "http://localhost:8080/api/v1/ping");
+    assert!(fake.client().ping().is_ok());
+    // Requests to jpdb's URL don't reach the fake anymore
+    let error = Client::with_transport("aaa", fake.clone())
+        .ping()
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Unhandled);
+}
+
+#[test]
+fn builder_bad_proxy() {
+    let resp = Client::builder("aaa").proxy("socks9://nope:nope").build();
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::Transport);
+}
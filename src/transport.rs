@@ -0,0 +1,231 @@
+use std::{fmt::Debug, io::Read, time::Duration};
+
+use ureq::Agent;
+
+use crate::{error::Error, request::Request};
+
+/// Error returned by a [`Transport`] when no response could be obtained at all
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A raw HTTP response, before any jpdb specific handling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Header names are compared case insensitively, see [`HttpResponse::header`]
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The delay asked for by the `Retry-After` header, when it's given in seconds
+    pub fn retry_after(&self) -> Option<Duration> {
+        let seconds = self.header("Retry-After")?.trim().parse::<f64>().ok()?;
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn into_json<T: serde::de::DeserializeOwned>(self) -> Result<T, std::io::Error> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Turns an error response into the matching [`Error`]
+    pub(crate) fn into_error(self) -> Error {
+        Error::from_response(self.status, &self.body)
+    }
+}
+
+/// The HTTP stack used by [`Client`](crate::client::Client) to reach jpdb.
+///
+/// Implement this to route requests through your own HTTP client, or to answer them in memory.
+/// Error statuses must be returned as a regular [`HttpResponse`], the client takes care of mapping
+/// them to an [`Error`].
+pub trait Transport: Send + Sync + Debug {
+    /// POSTs `request.body` as JSON to `request.url`, along with `headers`
+    fn send(
+        &self,
+        request: &Request,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, TransportError>;
+}
+
+/// The default [`Transport`], backed by a [`ureq::Agent`].
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    agent: Agent,
+    gzip: bool,
+}
+
+impl UreqTransport {
+    pub fn new() -> Self {
+        Self::from_agent(
+            ureq::AgentBuilder::new()
+                .timeout_read(Duration::from_secs(20))
+                .timeout_write(Duration::from_secs(5))
+                .build(),
+        )
+    }
+
+    pub fn from_agent(agent: Agent) -> Self {
+        Self { agent, gzip: true }
+    }
+
+    /// Whether responses may be gzip compressed, enabled by default
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    fn read_response(
+        result: Result<ureq::Response, ureq::Error>,
+    ) -> Result<HttpResponse, TransportError> {
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(e)) => return Err(Box::new(e)),
+        };
+        let mut http_response = HttpResponse::new(response.status(), Vec::new());
+        for name in response.headers_names() {
+            if let Some(value) = response.header(&name) {
+                http_response.headers.push((name.clone(), value.to_owned()));
+            }
+        }
+        response
+            .into_reader()
+            .read_to_end(&mut http_response.body)?;
+        Ok(http_response)
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for UreqTransport {
+    fn send(
+        &self,
+        request: &Request,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, TransportError> {
+        let mut prepared = self.agent.post(&request.url);
+        for (name, value) in headers {
+            prepared = prepared.set(name, value);
+        }
+        if !self.gzip {
+            prepared = prepared.set("Accept-Encoding", "identity");
+        }
+        Self::read_response(prepared.send_json(&request.body))
+    }
+}
+
+/// Future returned by [`AsyncTransport::send`]
+#[cfg(feature = "async")]
+pub type TransportFuture<'a> = std::pin::Pin<
+    Box<dyn std::future::Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>,
+>;
+
+/// The HTTP stack used by [`AsyncClient`](crate::async_client::AsyncClient) to reach jpdb,
+/// available with the `async` feature.
+///
+/// Every [`Transport`] is one too, answering in place, which is how
+/// [`FakeJpdb`](crate::testing::FakeJpdb) serves async clients.
+#[cfg(feature = "async")]
+pub trait AsyncTransport: Send + Sync + Debug {
+    /// POSTs `request.body` as JSON to `request.url`, along with `headers`
+    fn send<'a>(
+        &'a self,
+        request: &'a Request,
+        headers: &'a [(&'a str, &'a str)],
+    ) -> TransportFuture<'a>;
+}
+
+#[cfg(feature = "async")]
+impl<T: Transport> AsyncTransport for T {
+    fn send<'a>(
+        &'a self,
+        request: &'a Request,
+        headers: &'a [(&'a str, &'a str)],
+    ) -> TransportFuture<'a> {
+        Box::pin(std::future::ready(Transport::send(self, request, headers)))
+    }
+}
+
+/// The default [`AsyncTransport`], backed by a [`reqwest::Client`].
+#[cfg(feature = "async")]
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "async")]
+impl ReqwestTransport {
+    /// Fails with [`Error::Transport`] if the TLS backend couldn't be initialized
+    pub fn new() -> Result<Self, Error> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(20))
+            .build()?;
+        Ok(Self::from_client(client))
+    }
+
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    async fn post(
+        &self,
+        request: &Request,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, TransportError> {
+        let mut prepared = self.client.post(&request.url).json(&request.body);
+        for (name, value) in headers {
+            prepared = prepared.header(*name, *value);
+        }
+        let response = prepared.send().await?;
+        let mut http_response = HttpResponse::new(response.status().as_u16(), Vec::new());
+        for (name, value) in response.headers() {
+            if let Ok(value) = value.to_str() {
+                http_response
+                    .headers
+                    .push((name.as_str().to_owned(), value.to_owned()));
+            }
+        }
+        http_response.body = response.bytes().await?.to_vec();
+        Ok(http_response)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for ReqwestTransport {
+    fn send<'a>(
+        &'a self,
+        request: &'a Request,
+        headers: &'a [(&'a str, &'a str)],
+    ) -> TransportFuture<'a> {
+        Box::pin(self.post(request, headers))
+    }
+}
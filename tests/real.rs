@@ -1,17 +1,28 @@
-use std::{env, time::Duration};
+use std::sync::OnceLock;
 
 use jpdb::{
+    api_key::ApiKey,
     client::Client,
     error::ErrorKind,
+    governor::{RateLimit, RetryPolicy},
     request::{
-        AddVocabularyOptions, DeckQueryField, DeckVocabulary, SetCardSentenceOptions, Sid,
+        AddVocabularyOptions, AnyDeckWidget, Deck, DeckQueryField, SetCardSentenceOptions, Sid,
         UserDeckId, Vid, Vocabulary,
     },
 };
 
+/// Shared by the tests, which run in parallel and have to stay under jpdb's rate limit together
 fn get_good_client() -> Client {
-    std::thread::sleep(Duration::from_secs(5));
-    Client::new(&env::var("JPDB_TOKEN").unwrap_or_else(|_| String::from("")))
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            Client::builder(ApiKey::from_env().unwrap_or_else(|_| ApiKey::new("")))
+                .rate_limit(RateLimit::per_second(1))
+                .retry(RetryPolicy::default())
+                .build()
+                .unwrap()
+        })
+        .clone()
 }
 
 #[test]
@@ -88,7 +99,8 @@ fn jpdb_create_empty_deck_no_name() {
     let c = get_good_client();
     let resp = c.create_empty_deck("", None);
     assert!(resp.is_err());
-    assert_eq!(resp.unwrap_err().kind(), jpdb::error::ErrorKind::BadRequest);
+    // Caught before being sent
+    assert_eq!(resp.unwrap_err().kind(), ErrorKind::InvalidRequest);
 }
 
 #[test]
@@ -245,6 +257,33 @@ fn jpdb_list_vocabulary_with_occurences() {
     assert!(&resp.is_ok());
 }
 
-//TODO test list-special-decks
-// - no fields
-// - duplicated fields
+#[test]
+fn jpdb_list_special_decks_no_fields() {
+    let c = get_good_client();
+    let resp = c.list_special_decks(&[]);
+    dbg!(&resp);
+    assert!(resp.unwrap().iter().all(|deck| deck == &Deck::default()));
+}
+
+#[test]
+fn jpdb_list_user_decks() {
+    let c = get_good_client();
+    let resp = c.list_user_decks(&[DeckQueryField::Name, DeckQueryField::Id]);
+    dbg!(&resp);
+    assert!(resp
+        .unwrap()
+        .iter()
+        .all(|deck| deck.name.is_some() && deck.id.is_some()));
+}
+
+#[test]
+fn jpdb_list_all_decks() {
+    let c = get_good_client();
+    let resp = c.list_all_decks(&[DeckQueryField::Name]);
+    dbg!(&resp);
+    let decks = resp.unwrap();
+    assert!(decks
+        .iter()
+        .any(|(id, _)| *id == AnyDeckWidget::NeverForget));
+    assert!(decks.iter().any(|(id, _)| *id == AnyDeckWidget::Blacklist));
+}